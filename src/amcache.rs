@@ -1,56 +1,326 @@
-use forensic_rs::{err::ForensicResult, traits::registry::{RegHiveKey, RegistryReader}};
+use std::{borrow::Cow, collections::BTreeMap, path::Path};
 
-use crate::common::{app::InventoryApplicationIter, app_file::InventoryApplicationFileIter, app_shortcut::InventoryApplicationShortcutIter, dev_container::InventoryDeviceContainerIter, drv_binary::InventoryDriverBinaryIter, drv_package::InventoryDriverPackageIter};
+use forensic_rs::{err::{ForensicError, ForensicResult}, traits::{registry::{auto_close_key, RegHiveKey, RegValue, RegistryKeyInfo, RegistryReader}, vfs::{VirtualFile, VirtualFileSystem}}};
+use frnsc_hive::reader::{open_hive_with_logs, HiveRegistryReader};
+
+use crate::common::{values::{ParseMode, ParseOptions}, census::{read_device_census, DeviceCensus}, app::InventoryApplicationIter, app_appv::InventoryApplicationAppVIter, app_driver::InventoryApplicationDriverIter, app_file::InventoryApplicationFileIter, app_framework::InventoryApplicationFrameworkIter, app_shortcut::InventoryApplicationShortcutIter, dev_container::InventoryDeviceContainerIter, dev_interface::InventoryDeviceInterfaceIter, dev_media_class::InventoryDeviceMediaClassIter, dev_pnp::InventoryDevicePnpIter, dev_usbhub::InventoryDeviceUsbHubClassIter, drv_binary::InventoryDriverBinaryIter, drv_package::{InventoryDriverPackage, InventoryDriverPackageIter}, drv_package_ext::DriverPackageExtendedIter, format::{ie_version, install_time_version, windows_version, FormatInfo, OsVersionHint, OsVersionSource}, legacy_file::LegacyFileEntryIter, legacy_program::LegacyProgramEntryIter, misc_memory_slot::InventoryMiscellaneousMemorySlotArrayInfoIter, misc_uup::InventoryMiscellaneousUUPInfoIter, office::Office, schema::{BuiltinRecord, Schema, SchemaIter}};
 
 #[path ="./tst.rs"]
 #[cfg(test)]
 mod tst;
 
+/// Default location of the AmCache hive in a Windows filesystem
+pub const DEFAULT_AMCACHE_PATH : &str = r"C:\Windows\AppCompat\Programs\Amcache.hve";
+/// Default name used to mount the AmCache hive in the registry reader
+pub const DEFAULT_MOUNT_NAME : &str = "Amcache";
+
 pub struct AmCache<R : RegistryReader> {
-    reader : R
+    reader : R,
+    root_key : RegHiveKey,
+    root_path : String,
+    options : ParseOptions
+}
+
+/// Configures how an AmCache is opened from a RegistryReader.
+///
+/// The hive root (the key that contains `Root`) is located by opening `root_path` from `root_key`. By default this is `RegHiveKey::Hkey(0)` + "Amcache", which is how frnsc-hive exposes a hive added with `add_other("Amcache", ...)`.
+///
+/// ```rust,ignore
+/// let am_cache = AmCache::builder(reader).mount_name(r"HOST01\Amcache").build();
+/// ```
+pub struct AmCacheBuilder<R : RegistryReader> {
+    reader : R,
+    root_key : RegHiveKey,
+    root_path : String,
+    options : ParseOptions
+}
+
+impl<R : RegistryReader> AmCacheBuilder<R> {
+    /// Name used to mount the AmCache hive in the registry reader. Defaults to "Amcache".
+    pub fn mount_name(self, name : &str) -> Self {
+        self.root_key(RegHiveKey::Hkey(0)).root_path(name)
+    }
+
+    /// Key from which the hive root is opened. Defaults to `RegHiveKey::Hkey(0)`.
+    pub fn root_key(mut self, key : RegHiveKey) -> Self {
+        self.root_key = key;
+        self
+    }
+
+    /// Path prefix of the hive root relative to the root key. An empty path means the root key already points to the hive root.
+    pub fn root_path(mut self, path : &str) -> Self {
+        self.root_path = path.to_string();
+        self
+    }
+
+    /// How invalid values are handled. Defaults to ParseMode::Lenient.
+    pub fn parse_mode(mut self, mode : ParseMode) -> Self {
+        self.options.mode = mode;
+        self
+    }
+
+    /// Keep the data found after the NUL terminator of string values. Disabled by default. See ParseOptions::keep_slack for how it is encoded.
+    pub fn keep_slack(mut self, keep : bool) -> Self {
+        self.options.keep_slack = keep;
+        self
+    }
+
+    pub fn build(self) -> AmCache<R> {
+        AmCache {
+            reader : self.reader,
+            root_key : self.root_key,
+            root_path : self.root_path,
+            options : self.options
+        }
+    }
 }
 
 impl<R : RegistryReader> AmCache<R> {
+    /// Uses a RegistryReader that has the AmCache hive mounted as "Amcache"
+    pub fn new(reader : R) -> Self {
+        Self::builder(reader).build()
+    }
+
+    pub fn builder(reader : R) -> AmCacheBuilder<R> {
+        AmCacheBuilder {
+            reader,
+            root_key : RegHiveKey::Hkey(0),
+            root_path : DEFAULT_MOUNT_NAME.to_string(),
+            options : ParseOptions::default()
+        }
+    }
+
+    /// Access to the underlying RegistryReader
+    pub fn reader(&self) -> &R {
+        &self.reader
+    }
+
+    /// Returns the underlying RegistryReader
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+
+    fn open_subkey(&self, subkey : &str) -> ForensicResult<RegHiveKey> {
+        if self.root_path.is_empty() {
+            return self.reader.open_key(self.root_key, subkey)
+        }
+        let am_key = self.reader.open_key(self.root_key, &self.root_path)?;
+        let key = self.reader.open_key(am_key, subkey);
+        self.reader.close_key(am_key);
+        key
+    }
+
+    /// Records of a key parsed by the crate, read with its built-in schema
+    fn records<'a, T : BuiltinRecord>(&'a self) -> ForensicResult<SchemaIter<'a, R, T>> {
+        let key = self.open_subkey(&format!(r"Root\{}", T::KEY))?;
+        Ok(SchemaIter::new(&self.reader, key, self.options, Cow::Owned(T::schema())))
+    }
+
     /// A count of application shortcut objects in cache. Subkey: InventoryApplicationShortcut
     pub fn application_shortcuts<'a>(&'a self) -> ForensicResult<InventoryApplicationShortcutIter<'a, R>> {
-        let am_key = self.reader.open_key(RegHiveKey::Hkey(0), r"Amcache")?;
-        let key = self.reader.open_key(am_key, r"Root\InventoryApplicationShortcut")?;
-        self.reader.close_key(am_key);
-        Ok(InventoryApplicationShortcutIter { pos : 0, reader : &self.reader, key})
+        self.records()
+    }
+    /// App-V virtualized application packages. Subkey: InventoryApplicationAppV
+    pub fn application_appvs<'a>(&'a self) -> ForensicResult<InventoryApplicationAppVIter<'a, R>> {
+        self.records()
+    }
+    /// Drivers installed by each application. Subkey: InventoryApplicationDriver
+    pub fn application_drivers<'a>(&'a self) -> ForensicResult<InventoryApplicationDriverIter<'a, R>> {
+        self.records()
     }
     /// A count of application file objects in cache. Subkey: InventoryApplicationFile
     pub fn application_files<'a>(&'a self) -> ForensicResult<InventoryApplicationFileIter<'a, R>> {
-        let am_key = self.reader.open_key(RegHiveKey::Hkey(0), r"Amcache")?;
-        let key = self.reader.open_key(am_key, r"Root\InventoryApplicationFile")?;
-        self.reader.close_key(am_key);
-        Ok(InventoryApplicationFileIter { pos : 0, reader : &self.reader, key})
+        self.records()
+    }
+    /// Frameworks the application files depend on. Subkey: InventoryApplicationFramework
+    pub fn application_frameworks<'a>(&'a self) -> ForensicResult<InventoryApplicationFrameworkIter<'a, R>> {
+        self.records()
+    }
+    /// Host profile: OS, hardware, firmware, domain... Subkey: DeviceCensus
+    pub fn device_census(&self) -> ForensicResult<DeviceCensus> {
+        let key = self.open_subkey(r"Root\DeviceCensus")?;
+        auto_close_key(&self.reader, key, || read_device_census(&self.reader, key, self.options))
     }
     /// A count of device container objects in cache. Subkey: InventoryDeviceContainer
     pub fn device_containers<'a>(&'a self) -> ForensicResult<InventoryDeviceContainerIter<'a, R>> {
-        let am_key = self.reader.open_key(RegHiveKey::Hkey(0), r"Amcache")?;
-        let key = self.reader.open_key(am_key, r"Root\InventoryDeviceContainer")?;
-        self.reader.close_key(am_key);
-        Ok(InventoryDeviceContainerIter { pos : 0, reader : &self.reader, key})
+        self.records()
+    }
+    /// Sensor interfaces present in the device. Subkey: InventoryDeviceInterface
+    pub fn device_interfaces<'a>(&'a self) -> ForensicResult<InventoryDeviceInterfaceIter<'a, R>> {
+        self.records()
+    }
+    /// Class specific metadata of audio and camera devices. Subkey: InventoryDeviceMediaClass
+    pub fn device_media_classes<'a>(&'a self) -> ForensicResult<InventoryDeviceMediaClassIter<'a, R>> {
+        self.records()
+    }
+    /// A count of Plug and Play device objects in cache. Subkey: InventoryDevicePnp
+    pub fn device_pnps<'a>(&'a self) -> ForensicResult<InventoryDevicePnpIter<'a, R>> {
+        self.records()
+    }
+    /// USB hubs of the device. Subkey: InventoryDeviceUsbHubClass
+    pub fn device_usb_hubs<'a>(&'a self) -> ForensicResult<InventoryDeviceUsbHubClassIter<'a, R>> {
+        self.records()
     }
     /// A count of driver binary objects in cache. Subkey: InventoryDriverBinary
     pub fn driver_binaries<'a>(&'a self) -> ForensicResult<InventoryDriverBinaryIter<'a, R>> {
-        let am_key = self.reader.open_key(RegHiveKey::Hkey(0), r"Amcache")?;
-        let key = self.reader.open_key(am_key, r"Root\InventoryDriverBinary")?;
-        self.reader.close_key(am_key);
-        Ok(InventoryDriverBinaryIter { pos : 0, reader : &self.reader, key})
+        self.records()
     }
     /// A count of device objects in cache. Subkey: InventoryDriverPackage
     pub fn driver_package<'a>(&'a self) -> ForensicResult<InventoryDriverPackageIter<'a, R>> {
-        let am_key = self.reader.open_key(RegHiveKey::Hkey(0), r"Amcache")?;
-        let key = self.reader.open_key(am_key, r"Root\InventoryDriverPackage")?;
-        self.reader.close_key(am_key);
-        Ok(InventoryDriverPackageIter { pos : 0, reader : &self.reader, key})
+        self.records()
+    }
+    /// Extra metadata of the driver packages. Subkey: DriverPackageExtended
+    pub fn driver_packages_extended<'a>(&'a self) -> ForensicResult<DriverPackageExtendedIter<'a, R>> {
+        self.records()
+    }
+    /// Driver packages with their DriverPackageExtended entry attached, matched by key name. Hives without DriverPackageExtended return the packages as is.
+    pub fn driver_packages_with_extended(&self) -> ForensicResult<Vec<InventoryDriverPackage>> {
+        let mut extended : BTreeMap<String, _> = match self.driver_packages_extended() {
+            Ok(iter) => iter.map(|v| (v.key_name.to_lowercase(), v)).collect(),
+            Err(ForensicError::Missing(_)) => BTreeMap::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(self.driver_package()?.map(|mut package| {
+            if let Some(ext) = extended.remove(&package.key_name.to_lowercase()) {
+                package.extended = Some(ext);
+            }
+            package
+        }).collect())
+    }
+    /// Files of the legacy layout used before Windows 10 1709, for every volume. Subkey: File
+    pub fn legacy_files<'a>(&'a self) -> ForensicResult<LegacyFileEntryIter<'a, R>> {
+        let key = self.open_subkey(r"Root\File")?;
+        Ok(LegacyFileEntryIter::new(&self.reader, key, self.options))
+    }
+    /// Programs of the legacy layout used before Windows 10 1709. Subkey: Programs
+    pub fn legacy_programs<'a>(&'a self) -> ForensicResult<LegacyProgramEntryIter<'a, R>> {
+        self.records()
+    }
+    /// Memory modules plugged into the motherboard slots. Subkey: InventoryMiscellaneousMemorySlotArrayInfo
+    pub fn memory_slots<'a>(&'a self) -> ForensicResult<InventoryMiscellaneousMemorySlotArrayInfoIter<'a, R>> {
+        self.records()
+    }
+    /// Records of any key of Root described by a Schema: built-in, loaded from a file or created at runtime. Subkey: Schema::key
+    pub fn schema_records<'a>(&'a self, schema : &'a Schema) -> ForensicResult<SchemaIter<'a, R>> {
+        let key = self.open_subkey(&format!(r"Root\{}", schema.key))?;
+        Ok(SchemaIter::new(&self.reader, key, self.options, Cow::Borrowed(schema)))
+    }
+    /// Office add-ins, VBA macro usage, products and settings. Keys missing in the hive give empty lists. Subkeys: InventoryMiscellaneousOffice*
+    pub fn office(&self) -> ForensicResult<Office> {
+        Ok(Office {
+            add_ins : self.office_records()?,
+            add_in_usage : self.office_records()?,
+            identifiers : self.office_records()?,
+            ie_settings : self.office_records()?,
+            insights : self.office_records()?,
+            products : self.office_records()?,
+            settings : self.office_records()?,
+            vba : self.office_records()?,
+            vba_rule_violations : self.office_records()?,
+        })
+    }
+    fn office_records<T : BuiltinRecord>(&self) -> ForensicResult<Vec<T>> {
+        match self.records() {
+            Ok(iter) => Ok(iter.collect()),
+            Err(ForensicError::Missing(_)) => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+    /// Versions of the Unified Update Platform products. Subkey: InventoryMiscellaneousUUPInfo
+    pub fn uup_info<'a>(&'a self) -> ForensicResult<InventoryMiscellaneousUUPInfoIter<'a, R>> {
+        self.records()
     }
     /// A count of application objects in cache. Subkey: InventoryApplication
     pub fn applications<'a>(&'a self) -> ForensicResult<InventoryApplicationIter<'a, R>> {
-        let am_key = self.reader.open_key(RegHiveKey::Hkey(0), r"Amcache")?;
-        let key = self.reader.open_key(am_key, r"Root\InventoryApplication")?;
-        self.reader.close_key(am_key);
-        Ok(InventoryApplicationIter { pos : 0, reader : &self.reader, key})
+        self.records()
+    }
+    /// Layout of the hive, subkeys of Root and version of the OS that wrote it. Allows choosing the parsers for the hive and warning about the keys not supported. Subkey: Root
+    pub fn format_info(&self) -> ForensicResult<FormatInfo> {
+        let key = self.open_subkey("Root")?;
+        let subkeys = auto_close_key(&self.reader, key, || {
+            let mut subkeys = Vec::new();
+            while let Ok(name) = self.reader.key_at(key, subkeys.len() as u32) {
+                subkeys.push(name);
+            }
+            Ok(subkeys)
+        })?;
+        Ok(FormatInfo::new(subkeys, self.os_version_hint()))
     }
-}
\ No newline at end of file
+    /// OS version from the in-box drivers, else from the most recent OSVersionAtInstallTime, else from the IE version of the DeviceCensus
+    fn os_version_hint(&self) -> Option<OsVersionHint> {
+        let drivers = self.driver_binaries().ok().and_then(|drivers| drivers
+            .filter(|driver| driver.driver_in_box == 1)
+            .filter_map(|driver| Some((windows_version(&driver.driver_version)?, driver.driver_version)))
+            .max_by_key(|(version, _)| *version));
+        if let Some((version, raw)) = drivers {
+            return Some(OsVersionHint::new(version, raw, OsVersionSource::InBoxDriver))
+        }
+        let apps = self.applications().ok().and_then(|apps| apps
+            .filter_map(|app| Some((install_time_version(&app.os_version_at_install_time)?, app.os_version_at_install_time)))
+            .max_by_key(|(version, _)| *version));
+        if let Some((version, raw)) = apps {
+            return Some(OsVersionHint::new(version, raw, OsVersionSource::OsVersionAtInstallTime))
+        }
+        let census = self.device_census().ok()?;
+        let version = ie_version(census.ie_build()?)?;
+        Some(OsVersionHint::new(version, census.ie_version, OsVersionSource::IeVersion))
+    }
+}
+
+impl AmCache<HiveRegistryReader> {
+    /// Opens the AmCache hive located at `path` inside the filesystem. The transaction logs (.LOG1 and .LOG2) next to the hive are also loaded.
+    ///
+    /// Use DEFAULT_AMCACHE_PATH to locate the hive in its usual location: `C:\Windows\AppCompat\Programs\Amcache.hve`
+    pub fn from_fs(fs : &mut Box<dyn VirtualFileSystem>, path : &str) -> ForensicResult<Self> {
+        let (folder, name) = match path.rsplit_once(['\\', '/']) {
+            Some(("", name)) => (&path[..1], name),
+            Some(v) => v,
+            None => ("", path)
+        };
+        let hive_file = open_hive_with_logs(fs, Path::new(folder), name).ok_or_else(|| ForensicError::missing_string(format!("Cannot open AmCache hive {}", path)))?;
+        let mut reader = HiveRegistryReader::new();
+        reader.add_other(DEFAULT_MOUNT_NAME, hive_file);
+        Ok(Self::new(reader))
+    }
+}
+
+/// Allows sharing a single RegistryReader between multiple AmCache instances, ex: when several hives from different hosts are mounted in the same reader.
+///
+/// ```rust,ignore
+/// let host1 = AmCache::builder(RegistryReaderRef(&reader)).mount_name(r"HOST01\Amcache").build();
+/// let host2 = AmCache::builder(RegistryReaderRef(&reader)).mount_name(r"HOST02\Amcache").build();
+/// ```
+pub struct RegistryReaderRef<'a, R : RegistryReader>(pub &'a R);
+
+impl<'a, R : RegistryReader> RegistryReader for RegistryReaderRef<'a, R> {
+    fn from_file(&self, file: Box<dyn VirtualFile>) -> ForensicResult<Box<dyn RegistryReader>> {
+        self.0.from_file(file)
+    }
+    fn from_fs(&self, fs: Box<dyn VirtualFileSystem>) -> ForensicResult<Box<dyn RegistryReader>> {
+        self.0.from_fs(fs)
+    }
+    fn open_key(&self, hkey: RegHiveKey, key_name: &str) -> ForensicResult<RegHiveKey> {
+        self.0.open_key(hkey, key_name)
+    }
+    fn read_value(&self, hkey: RegHiveKey, value_name: &str) -> ForensicResult<RegValue> {
+        self.0.read_value(hkey, value_name)
+    }
+    fn enumerate_values(&self, hkey: RegHiveKey) -> ForensicResult<Vec<String>> {
+        self.0.enumerate_values(hkey)
+    }
+    fn enumerate_keys(&self, hkey: RegHiveKey) -> ForensicResult<Vec<String>> {
+        self.0.enumerate_keys(hkey)
+    }
+    fn key_at(&self, hkey: RegHiveKey, pos: u32) -> ForensicResult<String> {
+        self.0.key_at(hkey, pos)
+    }
+    fn value_at(&self, hkey: RegHiveKey, pos: u32) -> ForensicResult<String> {
+        self.0.value_at(hkey, pos)
+    }
+    fn key_info(&self, hkey: RegHiveKey) -> ForensicResult<RegistryKeyInfo> {
+        self.0.key_info(hkey)
+    }
+    fn close_key(&self, hkey: RegHiveKey) {
+        self.0.close_key(hkey)
+    }
+}
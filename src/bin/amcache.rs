@@ -0,0 +1,354 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    process::ExitCode,
+};
+
+use clap::{Parser, ValueEnum};
+use forensic_rs::{core::fs::StdVirtualFS, traits::vfs::VirtualFileSystem};
+use frnsc_amcache::{
+    amcache::AmCache,
+    common::{
+        format::{AmCacheGeneration, FormatInfo},
+        schema::Schema,
+        values::ParseMode,
+    },
+    export::CsvExporter,
+};
+use frnsc_hive::reader::HiveRegistryReader;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Triage tool for the AmCache.hve registry hive
+#[derive(Parser)]
+#[command(version, about)]
+struct Args {
+    /// Path to the Amcache.hve file. The .LOG1 and .LOG2 files next to it are applied automatically
+    hive: String,
+    /// Categories to extract. By default, all the categories whose keys exist in the hive
+    #[arg(short, long, value_delimiter = ',')]
+    category: Vec<Category>,
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = Format::Jsonl)]
+    format: Format,
+    /// Output file. Standard output by default. Output folder for the csv format
+    #[arg(short, long)]
+    output: Option<String>,
+    /// Fail the records with invalid values instead of using default values
+    #[arg(long)]
+    strict: bool,
+    /// Keep the data found after the NUL terminator of string values
+    #[arg(long)]
+    keep_slack: bool,
+    /// Schema of an extra key to extract, in TOML or JSON (.json extension). The records are written under the name of the key. Not exported in the csv format
+    #[arg(long)]
+    schema: Vec<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Category {
+    Census,
+    Office,
+    Files,
+    Apps,
+    Shortcuts,
+    Devices,
+    PnpDevices,
+    Drivers,
+    DriverPackages,
+    LegacyFiles,
+    LegacyPrograms,
+}
+
+impl Category {
+    const ALL: [Category; 11] = [
+        Category::Census,
+        Category::Office,
+        Category::Files,
+        Category::Apps,
+        Category::Shortcuts,
+        Category::Devices,
+        Category::PnpDevices,
+        Category::Drivers,
+        Category::DriverPackages,
+        Category::LegacyFiles,
+        Category::LegacyPrograms,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Category::Census => "census",
+            Category::Office => "office",
+            Category::Files => "files",
+            Category::Apps => "apps",
+            Category::Shortcuts => "shortcuts",
+            Category::Devices => "devices",
+            Category::PnpDevices => "pnp-devices",
+            Category::Drivers => "drivers",
+            Category::DriverPackages => "driver-packages",
+            Category::LegacyFiles => "legacy-files",
+            Category::LegacyPrograms => "legacy-programs",
+        }
+    }
+
+    /// Checks if the keys of the category exist in the hive
+    fn is_present(&self, info: &FormatInfo) -> bool {
+        match self {
+            Category::Census => info.has_subkey("DeviceCensus"),
+            Category::Office => info
+                .subkeys
+                .iter()
+                .any(|v| v.starts_with("InventoryMiscellaneousOffice")),
+            Category::Files => info.has_subkey("InventoryApplicationFile"),
+            Category::Apps => info.has_subkey("InventoryApplication"),
+            Category::Shortcuts => info.has_subkey("InventoryApplicationShortcut"),
+            Category::Devices => info.has_subkey("InventoryDeviceContainer"),
+            Category::PnpDevices => info.has_subkey("InventoryDevicePnp"),
+            Category::Drivers => info.has_subkey("InventoryDriverBinary"),
+            Category::DriverPackages => info.has_subkey("InventoryDriverPackage"),
+            Category::LegacyFiles => info.has_subkey("File"),
+            Category::LegacyPrograms => info.has_subkey("Programs"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// One JSON object per line with a "category" field
+    Jsonl,
+    /// A JSON object with an array of records for each category
+    Json,
+    /// Debug representation of each record
+    Text,
+    /// One CSV file per category with the layout of AmcacheParser. Requires an output folder. The census, the Office keys and the legacy keys are not exported
+    Csv,
+}
+
+type CliResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(args) {
+        Ok(_) => ExitCode::SUCCESS,
+        // Output closed early, ex: piped to head
+        Err(e) if is_broken_pipe(e.as_ref()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("amcache: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn is_broken_pipe(e: &(dyn std::error::Error + 'static)) -> bool {
+    let kind = match (
+        e.downcast_ref::<io::Error>(),
+        e.downcast_ref::<serde_json::Error>(),
+    ) {
+        (Some(e), _) => Some(e.kind()),
+        (_, Some(e)) => e.io_error_kind(),
+        _ => None,
+    };
+    kind == Some(io::ErrorKind::BrokenPipe)
+}
+
+fn run(args: Args) -> CliResult<()> {
+    let mut fs: Box<dyn VirtualFileSystem> = Box::new(StdVirtualFS::new());
+    let am_cache = AmCache::from_fs(&mut fs, &args.hive)?;
+    let mode = if args.strict {
+        ParseMode::Strict
+    } else {
+        ParseMode::Lenient
+    };
+    let am_cache = AmCache::builder(am_cache.into_reader())
+        .parse_mode(mode)
+        .keep_slack(args.keep_slack)
+        .build();
+    let schemas = args
+        .schema
+        .iter()
+        .map(|path| load_schema(path))
+        .collect::<CliResult<Vec<_>>>()?;
+    let info = am_cache.format_info()?;
+    if info.generation == AmCacheGeneration::Unknown {
+        eprintln!("amcache: warning: unknown layout, none of the known keys exist");
+    }
+    for key in info.unsupported_subkeys() {
+        if !schemas.iter().any(|v| v.key.eq_ignore_ascii_case(key)) {
+            eprintln!(
+                "amcache: warning: {} is not supported, use --schema to extract it",
+                key
+            );
+        }
+    }
+    let categories = if args.category.is_empty() {
+        Category::ALL
+            .into_iter()
+            .filter(|v| v.is_present(&info))
+            .collect()
+    } else {
+        args.category
+    };
+    if args.format == Format::Csv {
+        let folder = args
+            .output
+            .ok_or("The csv format requires an output folder")?;
+        std::fs::create_dir_all(&folder)?;
+        let exporter = CsvExporter::new(folder);
+        for category in categories {
+            write_csv(&am_cache, category, &exporter)?;
+        }
+        return Ok(());
+    }
+    let out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout().lock()),
+    };
+    let mut out = Output::new(BufWriter::new(out), args.format);
+    for category in categories {
+        out.start(category.name())?;
+        write_category(&am_cache, category, &mut out)?;
+    }
+    for schema in &schemas {
+        out.start(&schema.key)?;
+        am_cache
+            .schema_records(schema)?
+            .try_for_each(|v| out.record(&schema.key, &v))?;
+    }
+    out.finish()
+}
+
+fn load_schema(path: &str) -> CliResult<Schema> {
+    let data = std::fs::read_to_string(path)?;
+    let schema = if path.to_lowercase().ends_with(".json") {
+        Schema::from_json(&data)?
+    } else {
+        Schema::from_toml(&data)?
+    };
+    Ok(schema)
+}
+
+fn write_category(
+    am_cache: &AmCache<HiveRegistryReader>,
+    category: Category,
+    out: &mut Output,
+) -> CliResult<()> {
+    let name = category.name();
+    match category {
+        Category::Census => out.record(name, &am_cache.device_census()?),
+        Category::Office => out.record(name, &am_cache.office()?),
+        Category::Files => am_cache
+            .application_files()?
+            .try_for_each(|v| out.record(name, &v)),
+        Category::Apps => am_cache
+            .applications()?
+            .try_for_each(|v| out.record(name, &v)),
+        Category::Shortcuts => am_cache
+            .application_shortcuts()?
+            .try_for_each(|v| out.record(name, &v)),
+        Category::Devices => am_cache
+            .device_containers()?
+            .try_for_each(|v| out.record(name, &v)),
+        Category::PnpDevices => am_cache
+            .device_pnps()?
+            .try_for_each(|v| out.record(name, &v)),
+        Category::Drivers => am_cache
+            .driver_binaries()?
+            .try_for_each(|v| out.record(name, &v)),
+        Category::DriverPackages => am_cache
+            .driver_package()?
+            .try_for_each(|v| out.record(name, &v)),
+        Category::LegacyFiles => am_cache
+            .legacy_files()?
+            .try_for_each(|v| out.record(name, &v)),
+        Category::LegacyPrograms => am_cache
+            .legacy_programs()?
+            .try_for_each(|v| out.record(name, &v)),
+    }
+}
+
+fn write_csv(
+    am_cache: &AmCache<HiveRegistryReader>,
+    category: Category,
+    exporter: &CsvExporter,
+) -> CliResult<()> {
+    match category {
+        // The exporter has no AmcacheParser CSV layout for the DeviceCensus, the Office keys and the legacy keys
+        Category::Census | Category::Office | Category::LegacyFiles | Category::LegacyPrograms => {}
+        Category::Files => exporter.write_file_entries(am_cache)?,
+        Category::Apps => exporter.write_program_entries(am_cache)?,
+        Category::Shortcuts => exporter.write_shortcuts(am_cache)?,
+        Category::Devices => exporter.write_device_containers(am_cache)?,
+        Category::PnpDevices => exporter.write_device_pnps(am_cache)?,
+        Category::Drivers => exporter.write_driver_binaries(am_cache)?,
+        Category::DriverPackages => exporter.write_driver_packages(am_cache)?,
+    }
+    Ok(())
+}
+
+struct Output {
+    writer: BufWriter<Box<dyn Write>>,
+    format: Format,
+    first_category: bool,
+    first_record: bool,
+}
+
+impl Output {
+    fn new(writer: BufWriter<Box<dyn Write>>, format: Format) -> Self {
+        Self {
+            writer,
+            format,
+            first_category: true,
+            first_record: true,
+        }
+    }
+
+    fn start(&mut self, name: &str) -> CliResult<()> {
+        if self.format == Format::Json {
+            if self.first_category {
+                self.writer.write_all(b"{")?;
+            } else {
+                self.writer.write_all(b"],")?;
+            }
+            write!(self.writer, "\n{}:[", Value::from(name))?;
+        }
+        self.first_category = false;
+        self.first_record = true;
+        Ok(())
+    }
+
+    fn record<T: Serialize + std::fmt::Debug>(&mut self, name: &str, record: &T) -> CliResult<()> {
+        match self.format {
+            Format::Jsonl => {
+                let mut value = serde_json::to_value(record)?;
+                if let Value::Object(map) = &mut value {
+                    map.insert("category".into(), name.into());
+                }
+                serde_json::to_writer(&mut self.writer, &value)?;
+                self.writer.write_all(b"\n")?;
+            }
+            Format::Json => {
+                if !self.first_record {
+                    self.writer.write_all(b",")?;
+                }
+                self.writer.write_all(b"\n")?;
+                serde_json::to_writer(&mut self.writer, record)?;
+            }
+            Format::Text | Format::Csv => writeln!(self.writer, "{:?}", record)?,
+        }
+        self.first_record = false;
+        Ok(())
+    }
+
+    fn finish(mut self) -> CliResult<()> {
+        if self.format == Format::Json {
+            if self.first_category {
+                self.writer.write_all(b"{")?;
+            } else {
+                self.writer.write_all(b"]")?;
+            }
+            self.writer.write_all(b"}\n")?;
+        }
+        self.writer.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,19 @@
+use super::schema::{amcache_record, SchemaIter};
+
+amcache_record! {
+    /// Microsoft Application Virtualization (App-V) package. The applications of these packages run from a virtualized file system and registry.
+    pub struct InventoryApplicationAppV in "InventoryApplicationAppV" {
+        /// PackageGuid: App-V package identifier
+        pub package_guid: String = String("PackageGuid"),
+        /// VersionGuid: App-V package version identifier
+        pub version_guid: String = String("VersionGuid"),
+        /// Name
+        pub name: String = String("Name"),
+        /// Publisher
+        pub publisher: String = String("Publisher"),
+        /// Version
+        pub version: String = String("Version"),
+    }
+}
+
+pub type InventoryApplicationAppVIter<'a, R> = SchemaIter<'a, R, InventoryApplicationAppV>;
@@ -0,0 +1,32 @@
+use super::{
+    hash::Sha1Hash,
+    schema::{amcache_record, SchemaIter},
+};
+
+amcache_record! {
+    /// This event represents the drivers that an application installs. Links an InventoryDriverBinary (DriverId) with the InventoryApplication entries (ProgramIds) that installed it.
+    ///
+    /// https://learn.microsoft.com/en-us/previous-versions/windows/it-pro/privacy/basic-level-windows-diagnostic-events-and-fields-1803#microsoftwindowsinventorycoreinventoryapplicationdriveradd
+    pub struct InventoryApplicationDriver in "InventoryApplicationDriver" {
+        /// DriverId: FileId of the driver binary. The name of the subkey is used if the value does not exist
+        pub driver_id: String = String("DriverId"),
+        /// ProgramIds: programs associated with the driver
+        pub program_ids: Vec<String> = StringList("ProgramIds"),
+    }
+
+    fn complete(&mut self) -> ForensicResult<()> {
+        if self.driver_id.is_empty() {
+            self.driver_id = self.key_name.clone();
+        }
+        Ok(())
+    }
+}
+
+impl InventoryApplicationDriver {
+    /// SHA-1 of the driver obtained from the DriverId. None if the DriverId is empty or invalid.
+    pub fn sha1(&self) -> Option<Sha1Hash> {
+        self.driver_id.parse().ok()
+    }
+}
+
+pub type InventoryApplicationDriverIter<'a, R> = SchemaIter<'a, R, InventoryApplicationDriver>;
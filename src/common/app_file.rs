@@ -1,105 +1,68 @@
-use forensic_rs::{
-    info,
-    traits::registry::{auto_close_key, RegHiveKey, RegistryReader},
-    utils::time::Filetime,
-};
-
-use super::{read_value_string_or_empty, read_value_u32_or_empty, read_value_u64_or_empty};
-
-/// This event represents the basic metadata about a file on the system. The file must be part of an app and either have a block in the compatibility database or be part of an antivirus program.
-///
-/// https://learn.microsoft.com/en-us/previous-versions/windows/it-pro/privacy/basic-level-windows-diagnostic-events-and-fields-1803#microsoftwindowsappraisergeneralinventoryapplicationfileadd
-#[derive(Clone, Debug, Default)]
-pub struct InventoryApplicationFile {
-    /// LowerCaseLongPath
-    pub path: String,
-    /// ProductVersion
-    pub product_version: String,
-    /// ProductName
-    pub product_name: String,
-    /// ProgramId
-    pub program_id: String,
-    /// Last write timestamp
-    pub timestamp: Filetime,
-    /// FileId
-    pub hash: String,
-    /// Publisher
-    pub publisher: String,
-    /// LinkDate
-    pub link_date: String,
-    /// Language
-    pub language: u32,
-    /// Size
-    pub size: u64,
-    /// BinaryType
-    pub bin_type: String,
-    /// Usn
-    pub usn: u64,
-}
-
-pub struct InventoryApplicationFileIter<'a, R: RegistryReader> {
-    pub(crate) pos: u32,
-    pub(crate) key: RegHiveKey,
-    pub(crate) reader: &'a R,
-}
-
-impl<'a, R: RegistryReader> Iterator for InventoryApplicationFileIter<'a, R> {
-    type Item = InventoryApplicationFile;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.key == RegHiveKey::Hkey(0) {
-            return None;
-        }
-        let pos = self.pos;
-        self.pos += 1;
-        let next_subkey = self.reader.key_at(self.key, pos).ok()?;
-        let key = self.reader.open_key(self.key, &next_subkey).ok()?;
-        match auto_close_key(self.reader, key, || {
-            let path: String = read_value_string_or_empty(self.reader, key, "LowerCaseLongPath");
-            let product_version: String = match self.reader.read_value(key, "ProductVersion") {
-                Ok(v) => v,
-                Err(_) => self.reader.read_value(key, "BinProductVersion")?,
-            }
-            .try_into()?;
-            let product_name: String = read_value_string_or_empty(self.reader, key, "ProductName");
-            let program_id: String = read_value_string_or_empty(self.reader, key, "ProgramId");
-            let hash: String = read_value_string_or_empty(self.reader, key, "FileId");
-            let publisher: String = read_value_string_or_empty(self.reader, key, "Publisher");
+use forensic_rs::utils::time::Filetime;
 
-            let link_date: String = read_value_string_or_empty(self.reader, key, "LinkDate");
-            let language: u32 = read_value_u32_or_empty(self.reader, key, "Language");
-            let size: u64 = read_value_u64_or_empty(self.reader, key, "Size");
-            let bin_type: String = read_value_string_or_empty(self.reader, key, "BinaryType");
-            let usn: u64 = read_value_u64_or_empty(self.reader, key, "Usn");
+use super::{
+    hash::Sha1Hash,
+    schema::{amcache_record, SchemaIter},
+};
 
-            let key_info = self.reader.key_info(key)?;
-            Ok(InventoryApplicationFile {
-                path,
-                program_id,
-                timestamp: key_info.last_write_time,
-                product_version,
-                product_name,
-                hash,
-                publisher,
-                link_date,
-                language,
-                size,
-                bin_type,
-                usn,
-            })
-        }) {
-            Ok(v) => Some(v),
-            Err(e) => {
-                info!("Error getting AmCache File {}", e);
-                None
-            }
-        }
+amcache_record! {
+    /// This event represents the basic metadata about a file on the system. The file must be part of an app and either have a block in the compatibility database or be part of an antivirus program.
+    ///
+    /// https://learn.microsoft.com/en-us/previous-versions/windows/it-pro/privacy/basic-level-windows-diagnostic-events-and-fields-1803#microsoftwindowsappraisergeneralinventoryapplicationfileadd
+    pub struct InventoryApplicationFile in "InventoryApplicationFile" {
+        /// LowerCaseLongPath
+        pub path: String = String("LowerCaseLongPath"),
+        /// ProductVersion, or BinProductVersion in the hives that do not have it
+        pub product_version: String = String("ProductVersion", "BinProductVersion"),
+        /// ProductName
+        pub product_name: String = String("ProductName"),
+        /// ProgramId
+        pub program_id: String = String("ProgramId"),
+        /// FileId
+        pub hash: String = String("FileId"),
+        /// Publisher
+        pub publisher: String = String("Publisher"),
+        /// LinkDate
+        pub link_date: String = Date("LinkDate"),
+        /// LinkDate parsed as UTC
+        #[cfg_attr(feature = "serde", serde(default, with = "crate::common::time::iso8601_option"))]
+        pub link_date_filetime: Option<Filetime> = DateTime(link_date),
+        /// Language
+        pub language: u32 = U32("Language"),
+        /// Size
+        pub size: u64 = U64("Size"),
+        /// BinaryType
+        pub bin_type: String = String("BinaryType"),
+        /// Usn
+        pub usn: u64 = U64("Usn"),
+        /// Name: file name
+        pub name: String = String("Name"),
+        /// OriginalFileName: file name in the version resource. Differs from Name when the binary was renamed
+        pub original_file_name: String = String("OriginalFileName"),
+        /// Version
+        pub version: String = String("Version"),
+        /// BinFileVersion: file version in the version resource
+        pub bin_file_version: String = String("BinFileVersion"),
+        /// BinProductVersion: product version in the version resource
+        pub bin_product_version: String = String("BinProductVersion"),
+        /// IsOsComponent
+        pub is_os_component: u32 = U32("IsOsComponent"),
+        /// IsPeFile
+        pub is_pe_file: u32 = U32("IsPeFile"),
+        /// LongPathHash
+        pub long_path_hash: String = String("LongPathHash"),
+        /// AppxPackageFullName: package of a Store application
+        pub appx_package_full_name: String = String("AppxPackageFullName"),
+        /// AppxPackageRelativeId: application inside the package of a Store application
+        pub appx_package_relative_id: String = String("AppxPackageRelativeId"),
     }
 }
 
-impl<'a, R: RegistryReader> Drop for InventoryApplicationFileIter<'a, R> {
-    fn drop(&mut self) {
-        self.reader.close_key(self.key);
-        self.key = RegHiveKey::Hkey(0);
+impl InventoryApplicationFile {
+    /// SHA-1 of the file obtained from the FileId. None if the FileId is empty or invalid.
+    pub fn sha1(&self) -> Option<Sha1Hash> {
+        self.hash.parse().ok()
     }
 }
+
+pub type InventoryApplicationFileIter<'a, R> = SchemaIter<'a, R, InventoryApplicationFile>;
@@ -0,0 +1,28 @@
+use super::{
+    hash::Sha1Hash,
+    schema::{amcache_record, SchemaIter},
+};
+
+amcache_record! {
+    /// This event provides the basic metadata about the frameworks an application may depend on (.NET runtimes, Visual C++ redistributables...).
+    ///
+    /// https://learn.microsoft.com/en-us/previous-versions/windows/it-pro/privacy/basic-level-windows-diagnostic-events-and-fields-1803#microsoftwindowsinventorycoreinventoryapplicationframeworkadd
+    pub struct InventoryApplicationFramework in "InventoryApplicationFramework" {
+        /// FileId: hash of the file that depends on the frameworks
+        pub file_id: String = String("FileId"),
+        /// ProgramId: program the file belongs to
+        pub program_id: String = String("ProgramId"),
+        /// Frameworks: list of frameworks the file depends on
+        pub frameworks: Vec<String> = StringList("Frameworks"),
+    }
+}
+
+impl InventoryApplicationFramework {
+    /// SHA-1 of the file obtained from the FileId. None if the FileId is empty or invalid.
+    pub fn sha1(&self) -> Option<Sha1Hash> {
+        self.file_id.parse().ok()
+    }
+}
+
+pub type InventoryApplicationFrameworkIter<'a, R> =
+    SchemaIter<'a, R, InventoryApplicationFramework>;
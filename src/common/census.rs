@@ -0,0 +1,388 @@
+use std::collections::BTreeMap;
+
+use forensic_rs::{
+    err::{ForensicError, ForensicResult},
+    traits::registry::{auto_close_key, RegHiveKey, RegValue, RegistryReader},
+    utils::time::Filetime,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{
+    time::{from_iso8601, parse_date},
+    values::{AnomalyKind, ExtraValues, FieldAnomaly, ParseMode, ParseOptions, ValueReader},
+};
+
+/// Host profile collected by the Census task. Each area (OS, Processor, Memory, Firmware...) is stored in a subkey of DeviceCensus.
+///
+/// Unknown text values are stored as "#" by Windows and are returned as empty strings.
+///
+/// https://learn.microsoft.com/en-us/previous-versions/windows/it-pro/privacy/basic-level-windows-diagnostic-events-and-fields-1803#census-events
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DeviceCensus {
+    /// Name of the key: DeviceCensus
+    pub key_name: String,
+    /// Path of the key: `Root\DeviceCensus`
+    pub key_path: String,
+    /// App\IEVersion
+    pub ie_version: String,
+    /// OS\OSEdition: ex ServerStandardEval, Professional
+    pub os_edition: String,
+    /// OS\OSSKU
+    pub os_sku: u32,
+    /// OS\InstallationType: Client, Server...
+    pub installation_type: String,
+    /// OS\OSInstallType
+    pub os_install_type: u32,
+    /// OS\OSOOBEDateTime: end of the Out Of Box Experience, the first boot after the installation
+    pub os_oobe_date_time: String,
+    /// OS\OSOOBEDateTime parsed
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::common::time::iso8601_option"))]
+    pub os_oobe_filetime: Option<Filetime>,
+    /// OS\OSUILocale
+    pub os_ui_locale: String,
+    /// OS\InstallLanguage
+    pub install_language: String,
+    /// OS\LanguagePacks
+    pub language_packs: Vec<String>,
+    /// OS\DeviceTimeZone
+    pub device_time_zone: String,
+    /// OS\ActivationChannel
+    pub activation_channel: String,
+    /// OS\ProductKeyID2
+    pub product_key_id2: String,
+    /// OS\ProductActivationTime
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::common::time::iso8601_option"))]
+    pub product_activation_time: Option<Filetime>,
+    /// OS\GenuineState
+    pub genuine_state: u32,
+    /// OS\IsPortableOperatingSystem
+    pub is_portable_operating_system: u32,
+    /// OS\IsSecureBootEnabled
+    pub is_secure_boot_enabled: u32,
+    /// Processor\ProcessorManufacturer
+    pub processor_manufacturer: String,
+    /// Processor\ProcessorModel
+    pub processor_model: String,
+    /// Processor\ProcessorIdentifier
+    pub processor_identifier: String,
+    /// Processor\ProcessorArchitecture
+    pub processor_architecture: u32,
+    /// Processor\ProcessorClockSpeed in MHz
+    pub processor_clock_speed: u32,
+    /// Processor\ProcessorCores
+    pub processor_cores: u32,
+    /// Processor\ProcessorPhysicalCores
+    pub processor_physical_cores: u32,
+    /// Processor\SocketCount
+    pub socket_count: u32,
+    /// Memory\TotalPhysicalRAM in MB
+    pub total_physical_ram: u32,
+    /// Memory\TotalVisibleMemory in MB
+    pub total_visible_memory: u32,
+    /// Storage\PrimaryDiskTotalCapacity in MB
+    pub primary_disk_total_capacity: u64,
+    /// Storage\SystemVolumeTotalCapacity in MB
+    pub system_volume_total_capacity: u64,
+    /// Firmware\FirmwareManufacturer: BIOS vendor
+    pub firmware_manufacturer: String,
+    /// Firmware\FirmwareVersion
+    pub firmware_version: String,
+    /// Firmware\FirmwareReleaseDate
+    pub firmware_release_date: String,
+    /// Firmware\FirmwareReleaseDate parsed as UTC
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::common::time::iso8601_option"))]
+    pub firmware_release_date_filetime: Option<Filetime>,
+    /// Firmware\FirmwareType: 1 BIOS, 2 UEFI
+    pub firmware_type: u32,
+    /// Hardware\DeviceName: computer name
+    pub device_name: String,
+    /// Hardware\OEMManufacturerName
+    pub oem_manufacturer_name: String,
+    /// Hardware\OEMModelName
+    pub oem_model_name: String,
+    /// Hardware\OEMModelNumber
+    pub oem_model_number: String,
+    /// Hardware\OEMSerialNumber
+    pub oem_serial_number: String,
+    /// Hardware\ChassisType
+    pub chassis_type: u32,
+    /// Hardware\ComputerHardwareID
+    pub computer_hardware_id: String,
+    /// Hardware\InventoryId
+    pub inventory_id: String,
+    /// Hardware\TPMVersion
+    pub tpm_version: u32,
+    /// Enterprise\IsDomainJoined
+    pub is_domain_joined: u32,
+    /// Enterprise\HashedDomain
+    pub hashed_domain: String,
+    /// Enterprise\IsCloudDomainJoined: Azure AD joined
+    pub is_cloud_domain_joined: u32,
+    /// Enterprise\IsMDMEnrolled
+    pub is_mdm_enrolled: u32,
+    /// Enterprise\AADDeviceId
+    pub aad_device_id: String,
+    /// Enterprise\CommercialId
+    pub commercial_id: String,
+    /// VM\IsVirtualDevice
+    pub is_virtual_device: u32,
+    /// Network\NetworkAdapterGUID
+    pub network_adapter_guid: String,
+    /// WU\WUMachineId: Windows Update client identifier
+    pub wu_machine_id: String,
+    /// WU\WUServer: WSUS server
+    pub wu_server: String,
+    /// Last write timestamp of the DeviceCensus key
+    #[cfg_attr(feature = "serde", serde(with = "crate::common::time::iso8601"))]
+    pub timestamp: Filetime,
+    /// Values that could not be mapped to their field. The value name is prefixed with the subkey: `OS\OSEdition`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub anomalies: Vec<FieldAnomaly>,
+    /// Data found after the NUL terminator of string values, encoded as UTF-16LE. Only filled if ParseOptions::keep_slack is enabled
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub slack: BTreeMap<String, Vec<u8>>,
+    /// Values that are not mapped to a field: the values of the DeviceCensus key and, prefixed with the name of the subkey, the values of its subkeys. Ex: `WritePermissionsCheck`, `Xbox\XboxLiveDeviceId`
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::common::values::extra_values"))]
+    pub extra: BTreeMap<String, RegValue>,
+}
+
+impl DeviceCensus {
+    /// Third component of the IE version: 14393 in 11.2273.14393.0. It is not a version of the OS, but Internet Explorer 11 on Windows 10 and later uses the OS build there
+    pub fn ie_build(&self) -> Option<u32> {
+        self.ie_version.split('.').nth(2)?.parse().ok()
+    }
+}
+
+/// Reads the DeviceCensus subkeys into a single record
+struct CensusReader<'a, R: RegistryReader> {
+    reader: &'a R,
+    key: RegHiveKey,
+    options: ParseOptions,
+    anomalies: Vec<FieldAnomaly>,
+    slack: BTreeMap<String, Vec<u8>>,
+    extra: BTreeMap<String, RegValue>,
+    /// Subkeys mapped to fields
+    read_subkeys: Vec<&'static str>,
+}
+
+impl<'a, R: RegistryReader> CensusReader<'a, R> {
+    fn subkey<F>(
+        &mut self,
+        census: &mut DeviceCensus,
+        name: &'static str,
+        f: F,
+    ) -> ForensicResult<()>
+    where
+        F: FnOnce(&mut ValueReader, &mut DeviceCensus) -> ForensicResult<()>,
+    {
+        self.read_subkeys.push(name);
+        let key = match self.reader.open_key(self.key, name) {
+            Ok(v) => v,
+            Err(e) => {
+                let kind = match e {
+                    ForensicError::Missing(_) => AnomalyKind::Missing,
+                    _ if self.options.mode == ParseMode::Strict => return Err(e),
+                    _ => AnomalyKind::Corrupt,
+                };
+                self.anomalies.push(FieldAnomaly {
+                    value: name.to_string(),
+                    kind,
+                });
+                return Ok(());
+            }
+        };
+        auto_close_key(self.reader, key, || {
+            let mut values = ValueReader::new(self.reader, key, self.options);
+            f(&mut values, census)?;
+            let (anomalies, slack, extra) = values.finish();
+            self.anomalies.extend(anomalies.into_iter().map(|mut v| {
+                v.value = format!(r"{}\{}", name, v.value);
+                v
+            }));
+            self.slack.extend(
+                slack
+                    .into_iter()
+                    .map(|(k, v)| (format!(r"{}\{}", name, k), v)),
+            );
+            self.extend_extra(name, extra);
+            Ok(())
+        })
+    }
+
+    fn extend_extra(&mut self, subkey: &str, extra: ExtraValues) {
+        self.extra.extend(
+            extra
+                .into_iter()
+                .map(|(k, v)| (format!(r"{}\{}", subkey, k), v)),
+        );
+    }
+
+    /// Keeps the values of the subkeys without fields (Battery, Camera, Security...) in `extra`. A subkey that cannot be opened is reported as a Corrupt anomaly and never fails the census.
+    fn unparsed_subkeys(&mut self) {
+        let mut pos = 0;
+        while let Ok(name) = self.reader.key_at(self.key, pos) {
+            pos += 1;
+            if self
+                .read_subkeys
+                .iter()
+                .any(|v| v.eq_ignore_ascii_case(&name))
+            {
+                continue;
+            }
+            let key = match self.reader.open_key(self.key, &name) {
+                Ok(v) => v,
+                Err(_) => {
+                    self.anomalies.push(FieldAnomaly {
+                        value: name,
+                        kind: AnomalyKind::Corrupt,
+                    });
+                    continue;
+                }
+            };
+            let (_, _, extra) = ValueReader::new(self.reader, key, self.options).finish();
+            self.reader.close_key(key);
+            self.extend_extra(&name, extra);
+        }
+    }
+}
+
+/// Census text values use "#" when the value is unknown
+fn text(values: &mut ValueReader, name: &str) -> ForensicResult<String> {
+    let value = values.string(name)?;
+    Ok(if value == "#" { String::new() } else { value })
+}
+
+/// Census date parsed with `parse`. Values that cannot be parsed are kept as text and registered as Corrupt
+fn date(
+    values: &mut ValueReader,
+    name: &str,
+    parse: fn(&str) -> Option<Filetime>,
+) -> ForensicResult<(String, Option<Filetime>)> {
+    let raw = text(values, name)?;
+    let time = parse(&raw);
+    if time.is_none() && !raw.is_empty() {
+        values.anomaly(name, AnomalyKind::Corrupt)?;
+    }
+    Ok((raw, time))
+}
+
+pub(crate) fn read_device_census<R: RegistryReader>(
+    reader: &R,
+    key: RegHiveKey,
+    options: ParseOptions,
+) -> ForensicResult<DeviceCensus> {
+    let mut census = DeviceCensus {
+        key_name: "DeviceCensus".to_string(),
+        key_path: r"Root\DeviceCensus".to_string(),
+        timestamp: reader.key_info(key)?.last_write_time,
+        ..Default::default()
+    };
+    let mut census_reader = CensusReader {
+        reader,
+        key,
+        options,
+        anomalies: Vec::new(),
+        slack: BTreeMap::new(),
+        extra: ValueReader::new(reader, key, options).finish().2,
+        read_subkeys: Vec::new(),
+    };
+    census_reader.subkey(&mut census, "App", |values, census| {
+        census.ie_version = text(values, "IEVersion")?;
+        Ok(())
+    })?;
+    census_reader.subkey(&mut census, "OS", |values, census| {
+        census.os_edition = text(values, "OSEdition")?;
+        census.os_sku = values.u32("OSSKU")?;
+        census.installation_type = text(values, "InstallationType")?;
+        census.os_install_type = values.u32("OSInstallType")?;
+        let (date, filetime) = date(values, "OSOOBEDateTime", from_iso8601)?;
+        census.os_oobe_date_time = date;
+        census.os_oobe_filetime = filetime;
+        census.os_ui_locale = text(values, "OSUILocale")?;
+        census.install_language = text(values, "InstallLanguage")?;
+        census.language_packs = values.string_list("LanguagePacks")?;
+        census.device_time_zone = text(values, "DeviceTimeZone")?;
+        census.activation_channel = text(values, "ActivationChannel")?;
+        census.product_key_id2 = text(values, "ProductKeyID2")?;
+        census.product_activation_time = values
+            .u64_opt("ProductActivationTime")?
+            .filter(|v| *v != 0)
+            .map(Filetime::new);
+        census.genuine_state = values.u32("GenuineState")?;
+        census.is_portable_operating_system = values.u32("IsPortableOperatingSystem")?;
+        census.is_secure_boot_enabled = values.u32("IsSecureBootEnabled")?;
+        Ok(())
+    })?;
+    census_reader.subkey(&mut census, "Processor", |values, census| {
+        census.processor_manufacturer = text(values, "ProcessorManufacturer")?;
+        census.processor_model = text(values, "ProcessorModel")?;
+        census.processor_identifier = text(values, "ProcessorIdentifier")?;
+        census.processor_architecture = values.u32("ProcessorArchitecture")?;
+        census.processor_clock_speed = values.u32("ProcessorClockSpeed")?;
+        census.processor_cores = values.u32("ProcessorCores")?;
+        census.processor_physical_cores = values.u32("ProcessorPhysicalCores")?;
+        census.socket_count = values.u32("SocketCount")?;
+        Ok(())
+    })?;
+    census_reader.subkey(&mut census, "Memory", |values, census| {
+        census.total_physical_ram = values.u32("TotalPhysicalRAM")?;
+        census.total_visible_memory = values.u32("TotalVisibleMemory")?;
+        Ok(())
+    })?;
+    census_reader.subkey(&mut census, "Storage", |values, census| {
+        census.primary_disk_total_capacity = values.u64("PrimaryDiskTotalCapacity")?;
+        census.system_volume_total_capacity = values.u64("SystemVolumeTotalCapacity")?;
+        Ok(())
+    })?;
+    census_reader.subkey(&mut census, "Firmware", |values, census| {
+        census.firmware_manufacturer = text(values, "FirmwareManufacturer")?;
+        census.firmware_version = text(values, "FirmwareVersion")?;
+        let (date, filetime) = date(values, "FirmwareReleaseDate", parse_date)?;
+        census.firmware_release_date = date;
+        census.firmware_release_date_filetime = filetime;
+        census.firmware_type = values.u32("FirmwareType")?;
+        Ok(())
+    })?;
+    census_reader.subkey(&mut census, "Hardware", |values, census| {
+        census.device_name = text(values, "DeviceName")?;
+        census.oem_manufacturer_name = text(values, "OEMManufacturerName")?;
+        census.oem_model_name = text(values, "OEMModelName")?;
+        census.oem_model_number = text(values, "OEMModelNumber")?;
+        census.oem_serial_number = text(values, "OEMSerialNumber")?;
+        census.chassis_type = values.u32("ChassisType")?;
+        census.computer_hardware_id = text(values, "ComputerHardwareID")?;
+        census.inventory_id = text(values, "InventoryId")?;
+        census.tpm_version = values.u32("TPMVersion")?;
+        Ok(())
+    })?;
+    census_reader.subkey(&mut census, "Enterprise", |values, census| {
+        census.is_domain_joined = values.u32("IsDomainJoined")?;
+        census.hashed_domain = text(values, "HashedDomain")?;
+        census.is_cloud_domain_joined = values.u32("IsCloudDomainJoined")?;
+        census.is_mdm_enrolled = values.u32("IsMDMEnrolled")?;
+        census.aad_device_id = text(values, "AADDeviceId")?;
+        census.commercial_id = text(values, "CommercialId")?;
+        Ok(())
+    })?;
+    census_reader.subkey(&mut census, "VM", |values, census| {
+        census.is_virtual_device = values.u32("IsVirtualDevice")?;
+        Ok(())
+    })?;
+    census_reader.subkey(&mut census, "Network", |values, census| {
+        census.network_adapter_guid = text(values, "NetworkAdapterGUID")?;
+        Ok(())
+    })?;
+    census_reader.subkey(&mut census, "WU", |values, census| {
+        census.wu_machine_id = text(values, "WUMachineId")?;
+        census.wu_server = text(values, "WUServer")?;
+        Ok(())
+    })?;
+    census_reader.unparsed_subkeys();
+    census.anomalies = census_reader.anomalies;
+    census.slack = census_reader.slack;
+    census.extra = census_reader.extra;
+    Ok(census)
+}
@@ -0,0 +1,47 @@
+use super::schema::{amcache_record, SchemaIter};
+
+amcache_record! {
+    /// Sensor interfaces present in the device. Each value is 1 if at least one sensor of that kind was found.
+    pub struct InventoryDeviceInterface in "InventoryDeviceInterface" {
+        /// Accelerometer3D
+        pub accelerometer_3d: u32 = U32("Accelerometer3D"),
+        /// ActivityDetection
+        pub activity_detection: u32 = U32("ActivityDetection"),
+        /// AmbientLight
+        pub ambient_light: u32 = U32("AmbientLight"),
+        /// Barometer
+        pub barometer: u32 = U32("Barometer"),
+        /// Custom
+        pub custom: u32 = U32("Custom"),
+        /// EnergyMeter
+        pub energy_meter: u32 = U32("EnergyMeter"),
+        /// FloorElevation
+        pub floor_elevation: u32 = U32("FloorElevation"),
+        /// GeomagneticOrientation
+        pub geomagnetic_orientation: u32 = U32("GeomagneticOrientation"),
+        /// GravityVector
+        pub gravity_vector: u32 = U32("GravityVector"),
+        /// Gyrometer3D
+        pub gyrometer_3d: u32 = U32("Gyrometer3D"),
+        /// Humidity
+        pub humidity: u32 = U32("Humidity"),
+        /// LinearAccelerometer
+        pub linear_accelerometer: u32 = U32("LinearAccelerometer"),
+        /// Magnetometer3D
+        pub magnetometer_3d: u32 = U32("Magnetometer3D"),
+        /// Orientation
+        pub orientation: u32 = U32("Orientation"),
+        /// Pedometer
+        pub pedometer: u32 = U32("Pedometer"),
+        /// Proximity
+        pub proximity: u32 = U32("Proximity"),
+        /// RelativeOrientation
+        pub relative_orientation: u32 = U32("RelativeOrientation"),
+        /// SimpleDeviceOrientation
+        pub simple_device_orientation: u32 = U32("SimpleDeviceOrientation"),
+        /// Temperature
+        pub temperature: u32 = U32("Temperature"),
+    }
+}
+
+pub type InventoryDeviceInterfaceIter<'a, R> = SchemaIter<'a, R, InventoryDeviceInterface>;
@@ -0,0 +1,15 @@
+use super::schema::{amcache_record, SchemaIter};
+
+amcache_record! {
+    /// This event sends additional metadata about a Plug and Play device that is specific to a particular class of devices: audio endpoints, cameras...
+    ///
+    /// https://learn.microsoft.com/en-us/previous-versions/windows/it-pro/privacy/basic-level-windows-diagnostic-events-and-fields-1803#microsoftwindowsinventorycoreinventorydevicemediaclassadd
+    pub struct InventoryDeviceMediaClass in "InventoryDeviceMediaClass" {
+        /// Audio_CaptureDriver: audio device capture driver endpoint
+        pub audio_capture_driver: String = String("Audio_CaptureDriver"),
+        /// Audio_RenderDriver: audio device render driver endpoint
+        pub audio_render_driver: String = String("Audio_RenderDriver"),
+    }
+}
+
+pub type InventoryDeviceMediaClassIter<'a, R> = SchemaIter<'a, R, InventoryDeviceMediaClass>;
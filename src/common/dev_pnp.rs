@@ -0,0 +1,100 @@
+use forensic_rs::utils::time::Filetime;
+
+use super::{
+    hash::Sha1Hash,
+    schema::{amcache_record, SchemaIter},
+};
+
+amcache_record! {
+    /// This event sends basic metadata about a Plug and Play device: USB mass storage, HID, PCI, ACPI...
+    ///
+    /// https://learn.microsoft.com/en-us/previous-versions/windows/it-pro/privacy/basic-level-windows-diagnostic-events-and-fields-1803#microsoftwindowsinventorycoreinventorydevicepnpadd
+    pub struct InventoryDevicePnp in "InventoryDevicePnp" {
+        /// Model
+        pub model: String = String("Model"),
+        /// Manufacturer
+        pub manufacturer: String = String("Manufacturer"),
+        /// Description
+        pub description: String = String("Description"),
+        /// BusReportedDescription: description reported by the device itself
+        pub bus_reported_description: String = String("BusReportedDescription"),
+        /// Class: hidclass, usb, diskdrive...
+        pub class: String = String("Class"),
+        /// ClassGuid
+        pub class_guid: String = String("ClassGuid"),
+        /// Enumerator: bus that enumerated the device (usb, usbstor, pci, acpi...)
+        pub enumerator: String = String("Enumerator"),
+        /// HWID: comma separated list of hardware IDs
+        pub hwid: String = String("HWID"),
+        /// COMPID: comma separated list of compatible IDs
+        pub compid: String = String("COMPID"),
+        /// MatchingID: ID used to select the driver
+        pub matching_id: String = String("MatchingID"),
+        /// ParentId: instance ID of the parent device
+        pub parent_id: String = String("ParentId"),
+        /// ContainerId
+        pub container_id: String = String("ContainerId"),
+        /// Service
+        pub service: String = String("Service"),
+        /// STACKID: drivers of the device stack
+        pub stackid: String = String("STACKID"),
+        /// DriverName
+        pub driver_name: String = String("DriverName"),
+        /// DriverId: FileId of the driver binary
+        pub driver_id: String = String("DriverId"),
+        /// DriverPackageStrongName
+        pub driver_package_strong_name: String = String("DriverPackageStrongName"),
+        /// DriverVerVersion
+        pub driver_ver_version: String = String("DriverVerVersion"),
+        /// DriverVerDate
+        pub driver_ver_date: String = Date("DriverVerDate"),
+        /// DriverVerDate parsed as UTC
+        #[cfg_attr(feature = "serde", serde(default, with = "crate::common::time::iso8601_option"))]
+        pub driver_ver_date_filetime: Option<Filetime> = DateTime(driver_ver_date),
+        /// Inf
+        pub inf: String = String("Inf"),
+        /// ExtendedInfs
+        pub extended_infs: String = String("ExtendedInfs"),
+        /// Provider
+        pub provider: String = String("Provider"),
+        /// InstallState
+        pub install_state: u32 = U32("InstallState"),
+        /// DeviceState
+        pub device_state: u32 = U32("DeviceState"),
+        /// ProblemCode: device manager error code
+        pub problem_code: u32 = U32("ProblemCode"),
+        /// InstallDate
+        pub install_date: String = Date("InstallDate"),
+        /// InstallDate parsed as UTC
+        #[cfg_attr(feature = "serde", serde(default, with = "crate::common::time::iso8601_option"))]
+        pub install_date_filetime: Option<Filetime> = DateTime(install_date),
+        /// FirstInstallDate
+        pub first_install_date: String = Date("FirstInstallDate"),
+        /// FirstInstallDate parsed as UTC
+        #[cfg_attr(feature = "serde", serde(default, with = "crate::common::time::iso8601_option"))]
+        pub first_install_date_filetime: Option<Filetime> = DateTime(first_install_date),
+        /// UpperClassFilters
+        pub upper_class_filters: String = String("UpperClassFilters"),
+        /// LowerClassFilters
+        pub lower_class_filters: String = String("LowerClassFilters"),
+        /// UpperFilters
+        pub upper_filters: String = String("UpperFilters"),
+        /// LowerFilters
+        pub lower_filters: String = String("LowerFilters"),
+        /// DeviceInterfaceClasses
+        pub device_interface_classes: String = String("DeviceInterfaceClasses"),
+        /// DeviceDriverFlightId
+        pub device_driver_flight_id: String = String("DeviceDriverFlightId"),
+        /// DeviceExtDriversFlightIds
+        pub device_ext_drivers_flight_ids: String = String("DeviceExtDriversFlightIds"),
+    }
+}
+
+impl InventoryDevicePnp {
+    /// SHA-1 of the driver obtained from the DriverId. None if the DriverId is empty or invalid.
+    pub fn sha1(&self) -> Option<Sha1Hash> {
+        self.driver_id.parse().ok()
+    }
+}
+
+pub type InventoryDevicePnpIter<'a, R> = SchemaIter<'a, R, InventoryDevicePnp>;
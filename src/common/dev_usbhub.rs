@@ -0,0 +1,15 @@
+use super::schema::{amcache_record, SchemaIter};
+
+amcache_record! {
+    /// This event sends basic metadata about the USB hubs on the device.
+    ///
+    /// https://learn.microsoft.com/en-us/previous-versions/windows/it-pro/privacy/basic-level-windows-diagnostic-events-and-fields-1803#microsoftwindowsinventorycoreinventorydeviceusbhubclassadd
+    pub struct InventoryDeviceUsbHubClass in "InventoryDeviceUsbHubClass" {
+        /// TotalUserConnectablePorts: ports the user can connect devices to
+        pub total_user_connectable_ports: u32 = U32("TotalUserConnectablePorts"),
+        /// TotalUserConnectableTypeCPorts: USB Type-C ports the user can connect devices to
+        pub total_user_connectable_type_c_ports: u32 = U32("TotalUserConnectableTypeCPorts"),
+    }
+}
+
+pub type InventoryDeviceUsbHubClassIter<'a, R> = SchemaIter<'a, R, InventoryDeviceUsbHubClass>;
@@ -1,97 +1,36 @@
-use forensic_rs::{
-    info,
-    traits::registry::{auto_close_key, RegHiveKey, RegistryReader},
-    utils::time::Filetime,
-};
-
-use super::{read_value_string_or_empty, read_value_u32_or_empty};
-
-/// This event sends basic metadata about drive packages installed on the system.
-///
-/// https://learn.microsoft.com/en-us/previous-versions/windows/it-pro/privacy/basic-level-windows-diagnostic-events-and-fields-1803#microsoftwindowsinventorycoreinventorydriverpackageadd
-#[derive(Clone, Debug, Default)]
-pub struct InventoryDriverPackage {
-    pub class_guid: String,
-    pub class: String,
-    pub directory: String,
-    pub date: String,
-    pub version: String,
-    pub provider: String,
-    pub submission_id: String,
-    pub driver_inbox: u32,
-    pub inf: String,
-    pub flight_ids: String,
-    pub recovery_ids: String,
-    pub is_active: u32,
-    pub hwids: String,
-    pub sysfile: String,
-    /// Last write timestamp
-    pub timestamp: Filetime,
-}
-pub struct InventoryDriverPackageIter<'a, R: RegistryReader> {
-    pub(crate) pos: u32,
-    pub(crate) key: RegHiveKey,
-    pub(crate) reader: &'a R,
-}
+use forensic_rs::utils::time::Filetime;
 
-impl<'a, R: RegistryReader> Iterator for InventoryDriverPackageIter<'a, R> {
-    type Item = InventoryDriverPackage;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.key == RegHiveKey::Hkey(0) {
-            return None;
-        }
-        let pos = self.pos;
-        self.pos += 1;
-        let next_subkey = self.reader.key_at(self.key, pos).ok()?;
-        let key = self.reader.open_key(self.key, &next_subkey).ok()?;
-        match auto_close_key(self.reader, key, || {
-            let class_guid = read_value_string_or_empty(self.reader, key, "ClassGuid");
-            let class = read_value_string_or_empty(self.reader, key, "Class");
-            let directory = read_value_string_or_empty(self.reader, key, "Directory");
-            let date = read_value_string_or_empty(self.reader, key, "Date");
-            let version = read_value_string_or_empty(self.reader, key, "Version");
-            let provider = read_value_string_or_empty(self.reader, key, "Provider");
-            let submission_id = read_value_string_or_empty(self.reader, key, "SubmissionId");
-            let driver_inbox = read_value_u32_or_empty(self.reader, key, "DriverInBox");
-            let inf = read_value_string_or_empty(self.reader, key, "Inf");
-            let flight_ids = read_value_string_or_empty(self.reader, key, "FlightIds");
-            let recovery_ids = read_value_string_or_empty(self.reader, key, "RecoveryIds");
-            let is_active = read_value_u32_or_empty(self.reader, key, "IsActive");
-            let hwids = read_value_string_or_empty(self.reader, key, "Hwids");
-            let sysfile = read_value_string_or_empty(self.reader, key, "SYSFILE");
+use super::{
+    drv_package_ext::DriverPackageExtended,
+    schema::{amcache_record, SchemaIter},
+};
 
-            let key_info = self.reader.key_info(key)?;
-            Ok(InventoryDriverPackage {
-                class_guid,
-                class,
-                directory,
-                date,
-                version,
-                provider,
-                submission_id,
-                driver_inbox,
-                inf,
-                flight_ids,
-                recovery_ids,
-                is_active,
-                hwids,
-                sysfile,
-                timestamp: key_info.last_write_time,
-            })
-        }) {
-            Ok(v) => Some(v),
-            Err(e) => {
-                info!("Error getting AmCache File {}", e);
-                None
-            }
-        }
+amcache_record! {
+    /// This event sends basic metadata about drive packages installed on the system.
+    ///
+    /// https://learn.microsoft.com/en-us/previous-versions/windows/it-pro/privacy/basic-level-windows-diagnostic-events-and-fields-1803#microsoftwindowsinventorycoreinventorydriverpackageadd
+    pub struct InventoryDriverPackage in "InventoryDriverPackage" {
+        pub class_guid: String = String("ClassGuid"),
+        pub class: String = String("Class"),
+        pub directory: String = String("Directory"),
+        pub date: String = Date("Date"),
+        /// Date parsed as UTC
+        #[cfg_attr(feature = "serde", serde(default, with = "crate::common::time::iso8601_option"))]
+        pub date_filetime: Option<Filetime> = DateTime(date),
+        pub version: String = String("Version"),
+        pub provider: String = String("Provider"),
+        pub submission_id: String = String("SubmissionId"),
+        pub driver_inbox: u32 = U32("DriverInBox"),
+        pub inf: String = String("Inf"),
+        pub flight_ids: String = String("FlightIds"),
+        pub recovery_ids: String = String("RecoveryIds"),
+        pub is_active: u32 = U32("IsActive"),
+        pub hwids: String = String("Hwids"),
+        pub sysfile: String = String("SYSFILE"),
+        /// Matching DriverPackageExtended entry. Only filled by AmCache::driver_packages_with_extended
+        #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+        pub extended: Option<DriverPackageExtended>,
     }
 }
 
-impl<'a, R: RegistryReader> Drop for InventoryDriverPackageIter<'a, R> {
-    fn drop(&mut self) {
-        self.reader.close_key(self.key);
-        self.key = RegHiveKey::Hkey(0);
-    }
-}
+pub type InventoryDriverPackageIter<'a, R> = SchemaIter<'a, R, InventoryDriverPackage>;
@@ -0,0 +1,15 @@
+use std::collections::BTreeMap;
+
+use super::schema::{amcache_record, SchemaIter};
+
+amcache_record! {
+    /// Extra metadata of a driver package. The subkey has the same name as the InventoryDriverPackage subkey it extends (ex: `balloon.inf_amd64_b42039c1c5afb07d`).
+    ///
+    /// The values of this key are not documented, so all of them are kept as text: numbers in decimal and binary data in hexadecimal.
+    pub struct DriverPackageExtended in "DriverPackageExtended" {
+        /// Values of the subkey
+        pub values: BTreeMap<String, String> = OtherValues(Text),
+    }
+}
+
+pub type DriverPackageExtendedIter<'a, R> = SchemaIter<'a, R, DriverPackageExtended>;
@@ -0,0 +1,158 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::schema::Schema;
+
+/// Subkeys of Root parsed by this crate without a built-in schema
+const SUBKEYS_WITHOUT_SCHEMA: [&str; 2] = ["DeviceCensus", "File"];
+
+/// Subkeys of Root parsed by this crate: the keys of `Schema::builtin_keys`, DeviceCensus and File
+pub fn supported_subkeys() -> impl Iterator<Item = &'static str> {
+    SUBKEYS_WITHOUT_SCHEMA
+        .into_iter()
+        .chain(Schema::builtin_keys())
+}
+
+/// First build of Windows 10
+const WINDOWS_10_BUILD: u32 = 10240;
+/// First build of Windows 11
+const WINDOWS_11_BUILD: u32 = 22000;
+
+/// Layout of the hive. It depends on the version of the compatibility appraiser that wrote it, so a Windows 10 1607 updated by later patches can use the Inventory layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "snake_case"))]
+pub enum AmCacheGeneration {
+    /// Windows 8, Windows Server 2012 and Windows 10 before 1607: `Root\File` and `Root\Programs`
+    Legacy,
+    /// Windows 10 1607: the legacy keys along the first Inventory keys
+    Mixed,
+    /// Windows 10 1709 and later: Inventory keys only
+    Inventory,
+    /// Windows 7 and 8.1 updated with a recent compatibility appraiser: Inventory keys, with or without the legacy keys, written by a build older than Windows 10
+    Downlevel,
+    /// Windows 11: Inventory keys written by a build 22000 or later
+    Windows11,
+    /// None of the known keys exist
+    Unknown,
+}
+
+/// Where the OS version hint was found
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "snake_case"))]
+pub enum OsVersionSource {
+    /// Highest DriverVersion of the in-box drivers of InventoryDriverBinary: they ship with the OS and carry its version, ex: 10.0.14393.2969
+    InBoxDriver,
+    /// Highest OSVersionAtInstallTime of InventoryApplication: version of the OS when the last application was installed
+    OsVersionAtInstallTime,
+    /// DeviceCensus App\IEVersion. Only a fallback: it is the version of Internet Explorer, whose third component matches the OS build on Windows 10, ex: 11.2273.14393.0
+    IeVersion,
+}
+
+/// Version of the OS that wrote the hive
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OsVersionHint {
+    /// OS version: major, minor and build. Ex: 10.0.14393 or 6.3.9600
+    pub version: String,
+    /// OS build number. Ex: 14393
+    pub build: u32,
+    /// Value the version was taken from, as stored in the hive. Ex: 10.0.14393.2969, 10.0.0.14393 or 11.2273.14393.0
+    pub raw: String,
+    /// Where the version was found
+    pub source: OsVersionSource,
+}
+
+impl OsVersionHint {
+    pub(crate) fn new(version: WindowsVersion, raw: String, source: OsVersionSource) -> Self {
+        let [major, minor, build, _] = version;
+        Self {
+            version: format!("{}.{}.{}", major, minor, build),
+            build,
+            raw,
+            source,
+        }
+    }
+}
+
+/// Layout of the hive returned by `AmCache::format_info`
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FormatInfo {
+    /// Detected layout
+    pub generation: AmCacheGeneration,
+    /// Subkeys of Root
+    pub subkeys: Vec<String>,
+    /// Version of the OS. None if the hive has no in-box driver and no InventoryApplication with a Windows version, and no DeviceCensus with a Windows 10 or later IE version
+    pub os_version: Option<OsVersionHint>,
+}
+
+impl FormatInfo {
+    pub(crate) fn new(subkeys: Vec<String>, os_version: Option<OsVersionHint>) -> Self {
+        let legacy = subkeys
+            .iter()
+            .any(|v| v.eq_ignore_ascii_case("File") || v.eq_ignore_ascii_case("Programs"));
+        let inventory = subkeys
+            .iter()
+            .any(|v| v.to_ascii_lowercase().starts_with("inventory"));
+        let build = os_version.as_ref().map(|v| v.build);
+        let generation = match (legacy, inventory, build) {
+            (_, true, Some(build)) if build < WINDOWS_10_BUILD => AmCacheGeneration::Downlevel,
+            (true, false, _) => AmCacheGeneration::Legacy,
+            (true, true, _) => AmCacheGeneration::Mixed,
+            (false, true, Some(build)) if build >= WINDOWS_11_BUILD => AmCacheGeneration::Windows11,
+            (false, true, _) => AmCacheGeneration::Inventory,
+            (false, false, _) => AmCacheGeneration::Unknown,
+        };
+        Self {
+            generation,
+            subkeys,
+            os_version,
+        }
+    }
+
+    /// Checks if Root contains the subkey
+    pub fn has_subkey(&self, name: &str) -> bool {
+        self.subkeys.iter().any(|v| v.eq_ignore_ascii_case(name))
+    }
+
+    /// Subkeys of Root not parsed by this crate. They can be read with a `Schema`
+    pub fn unsupported_subkeys(&self) -> Vec<&str> {
+        self.subkeys
+            .iter()
+            .filter(|v| !supported_subkeys().any(|s| s.eq_ignore_ascii_case(v)))
+            .map(|v| v.as_str())
+            .collect()
+    }
+}
+
+/// Major, minor, build and revision of a Windows version
+pub(crate) type WindowsVersion = [u32; 4];
+
+/// Parses a Windows version like `10.0.14393.2969` or `6.3.9600.17415`. None unless it is Windows Vista to 8.1 (6.x) or Windows 10 and later (10.0)
+pub(crate) fn windows_version(version: &str) -> Option<WindowsVersion> {
+    let mut parts = version.split('.').map(|v| v.parse::<u32>().ok());
+    let version = [
+        parts.next()??,
+        parts.next()??,
+        parts.next()??,
+        parts.next().unwrap_or(Some(0))?,
+    ];
+    match (parts.next(), version) {
+        (None, [6, 0..=3, ..] | [10, 0, ..]) => Some(version),
+        _ => None,
+    }
+}
+
+/// Parses an OSVersionAtInstallTime value. The build is the third component, or the fourth when the third is 0 like in `10.0.0.14393`
+pub(crate) fn install_time_version(version: &str) -> Option<WindowsVersion> {
+    match windows_version(version)? {
+        [_, _, 0, 0] => None,
+        [major, minor, 0, build] => Some([major, minor, build, 0]),
+        version => Some(version),
+    }
+}
+
+/// Windows 10 version of a DeviceCensus IE version build. Builds older than Windows 10 are rejected: Internet Explorer only reuses the OS build on Windows 10 and later
+pub(crate) fn ie_version(build: u32) -> Option<WindowsVersion> {
+    (build >= WINDOWS_10_BUILD).then_some([10, 0, build, 0])
+}
@@ -0,0 +1,87 @@
+use std::{fmt, str::FromStr};
+
+use forensic_rs::err::ForensicError;
+
+/// SHA-1 hash of a file. The AmCache stores it in FileId and DriverId as 44 hex characters: the "0000" prefix followed by the hash.
+///
+/// ```rust
+/// use frnsc_amcache::common::hash::Sha1Hash;
+/// let hash : Sha1Hash = "0000f65dd072877889a3ff2a18d76d9ad904264c0921".parse().unwrap();
+/// assert_eq!("f65dd072877889a3ff2a18d76d9ad904264c0921", hash.to_string());
+/// assert_eq!(hash, "F65DD072877889A3FF2A18D76D9AD904264C0921".parse().unwrap());
+/// assert!("0000+65dd072877889a3ff2a18d76d9ad904264c0921".parse::<Sha1Hash>().is_err());
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Sha1Hash(pub [u8; 20]);
+
+impl Sha1Hash {
+    pub fn as_bytes(&self) -> &[u8; 20] {
+        &self.0
+    }
+}
+
+impl FromStr for Sha1Hash {
+    type Err = ForensicError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = match s.len() {
+            40 => s,
+            44 if s.starts_with("0000") => &s[4..],
+            _ => {
+                return Err(ForensicError::bad_format_string(format!(
+                    "Invalid SHA-1 length: {}",
+                    s
+                )))
+            }
+        };
+        if !hex.bytes().all(|v| v.is_ascii_hexdigit()) {
+            return Err(ForensicError::bad_format_string(format!(
+                "Invalid SHA-1 hex string: {}",
+                s
+            )));
+        }
+        let mut hash = [0u8; 20];
+        for (byte, chunk) in hash.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            *byte = (hex_value(chunk[0]) << 4) | hex_value(chunk[1]);
+        }
+        Ok(Self(hash))
+    }
+}
+
+fn hex_value(c: u8) -> u8 {
+    match c {
+        b'0'..=b'9' => c - b'0',
+        b'a'..=b'f' => c - b'a' + 10,
+        _ => c - b'A' + 10,
+    }
+}
+
+impl fmt::Display for Sha1Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Sha1Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Sha1Hash({})", self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Sha1Hash {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Sha1Hash {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
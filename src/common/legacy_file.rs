@@ -0,0 +1,193 @@
+use forensic_rs::{
+    err::{ForensicError, ForensicResult},
+    traits::registry::{auto_close_key, RegHiveKey, RegistryReader},
+    utils::time::Filetime,
+};
+
+use super::{
+    hash::Sha1Hash,
+    next_valid_record, record_path,
+    schema::{amcache_record, BuiltinRecord, Record, Schema},
+    values::ParseOptions,
+    RecordIter,
+};
+
+amcache_record! {
+    /// File executed or installed in Windows 8, Windows Server 2012 and Windows 10 before 1709. Stored in `Root\File\{VolumeGuid}\{FileReference}` with numbered values. The last write time of the subkey is the first execution of the file.
+    ///
+    /// https://www.ssi.gouv.fr/uploads/2019/01/anssi-coriin_2019-analysis_amcache.pdf
+    pub struct LegacyFileEntry in "File" {
+        /// GUID of the volume containing the file: name of the parent key
+        pub volume_guid: String,
+        /// NTFS file reference: name of the subkey parsed as hexadecimal
+        pub file_reference: u64,
+        /// MFT entry number: lower 48 bits of the file reference
+        pub mft_entry_number: u64,
+        /// MFT sequence number: upper 16 bits of the file reference
+        pub mft_sequence_number: u16,
+        /// 0: ProductName
+        pub product_name: String = String("0"),
+        /// 1: CompanyName
+        pub company_name: String = String("1"),
+        /// 2: FileVersionNumber
+        pub file_version_number: String = String("2"),
+        /// 3: LanguageCode
+        pub language_code: u32 = U32("3"),
+        /// 4: SwitchBackContext
+        pub switch_back_context: u64 = U64("4"),
+        /// 5: FileVersion
+        pub file_version: String = String("5"),
+        /// 6: FileSize
+        pub file_size: u64 = U64("6"),
+        /// 7: SizeOfImage from the PE header
+        pub size_of_image: u32 = U32("7"),
+        /// 8: Hash of the PE header
+        pub pe_header_hash: String = String("8"),
+        /// 9: Checksum from the PE header
+        pub pe_header_checksum: u32 = U32("9"),
+        /// c: FileDescription
+        pub file_description: String = String("c"),
+        /// f: Linker compile time from the PE header
+        #[cfg_attr(feature = "serde", serde(default, with = "crate::common::time::iso8601_option"))]
+        pub compile_time: Option<Filetime> = UnixTime("f"),
+        /// 11: secondary last modification time. Other tools name it LastModified and the value 17 LastModifiedStore
+        #[cfg_attr(feature = "serde", serde(default, with = "crate::common::time::iso8601_option"))]
+        pub last_modified_alt: Option<Filetime> = Filetime("11"),
+        /// 12: Creation time of the file
+        #[cfg_attr(feature = "serde", serde(default, with = "crate::common::time::iso8601_option"))]
+        pub created: Option<Filetime> = Filetime("12"),
+        /// 15: Full path of the file
+        pub full_path: String = String("15"),
+        /// 17: Last modification time of the file
+        #[cfg_attr(feature = "serde", serde(default, with = "crate::common::time::iso8601_option"))]
+        pub last_modified: Option<Filetime> = Filetime("17"),
+        /// 100: Identifier of the program in Root\Programs that installed the file
+        pub program_id: String = String("100"),
+        /// 101: "0000" followed by the SHA-1 of the file
+        pub hash: String = String("101"),
+    }
+
+    fn complete(&mut self) -> ForensicResult<()> {
+        self.file_reference = u64::from_str_radix(&self.key_name, 16).map_err(|_| {
+            ForensicError::bad_format_string(format!("Invalid file reference: {}", self.key_name))
+        })?;
+        self.mft_entry_number = self.file_reference & 0xFFFF_FFFF_FFFF;
+        self.mft_sequence_number = (self.file_reference >> 48) as u16;
+        Ok(())
+    }
+}
+
+impl LegacyFileEntry {
+    /// SHA-1 of the file obtained from the value 101. None if it is empty or invalid.
+    pub fn sha1(&self) -> Option<Sha1Hash> {
+        self.hash.parse().ok()
+    }
+}
+
+/// Iterates over the files of every volume in `Root\File`
+pub struct LegacyFileEntryIter<'a, R: RegistryReader> {
+    volume_pos: u32,
+    pos: u32,
+    key: RegHiveKey,
+    volume: Option<(RegHiveKey, String)>,
+    reader: &'a R,
+    options: ParseOptions,
+    schema: Schema,
+}
+
+impl<'a, R: RegistryReader> LegacyFileEntryIter<'a, R> {
+    pub(crate) fn new(reader: &'a R, key: RegHiveKey, options: ParseOptions) -> Self {
+        Self {
+            volume_pos: 0,
+            pos: 0,
+            key,
+            volume: None,
+            reader,
+            options,
+            schema: LegacyFileEntry::schema(),
+        }
+    }
+
+    fn close_volume(&mut self) {
+        if let Some((key, _)) = self.volume.take() {
+            self.reader.close_key(key);
+        }
+    }
+
+    fn read_file(
+        &self,
+        volume_guid: &str,
+        key: RegHiveKey,
+        key_name: &str,
+    ) -> ForensicResult<LegacyFileEntry> {
+        let key_path = record_path(
+            LegacyFileEntry::KEY,
+            &format!(r"{}\{}", volume_guid, key_name),
+        );
+        let record = self
+            .schema
+            .read_record(self.reader, key, key_name, key_path, self.options)?;
+        let mut entry = LegacyFileEntry::from_record(record)?;
+        entry.volume_guid = volume_guid.to_string();
+        Ok(entry)
+    }
+}
+
+impl<'a, R: RegistryReader> RecordIter for LegacyFileEntryIter<'a, R> {
+    type Record = LegacyFileEntry;
+
+    fn next_record(&mut self) -> Option<ForensicResult<Self::Record>> {
+        if self.key == RegHiveKey::Hkey(0) {
+            return None;
+        }
+        loop {
+            let (volume, volume_guid) = match &self.volume {
+                Some((volume, guid)) => (*volume, guid.clone()),
+                None => {
+                    let volume_pos = self.volume_pos;
+                    self.volume_pos += 1;
+                    let guid = self.reader.key_at(self.key, volume_pos).ok()?;
+                    let volume = match self.reader.open_key(self.key, &guid) {
+                        Ok(v) => v,
+                        Err(e) => return Some(Err(e)),
+                    };
+                    self.pos = 0;
+                    self.volume = Some((volume, guid.clone()));
+                    (volume, guid)
+                }
+            };
+            let pos = self.pos;
+            self.pos += 1;
+            let next_subkey = match self.reader.key_at(volume, pos) {
+                Ok(v) => v,
+                Err(_) => {
+                    self.close_volume();
+                    continue;
+                }
+            };
+            let key = match self.reader.open_key(volume, &next_subkey) {
+                Ok(v) => v,
+                Err(e) => return Some(Err(e)),
+            };
+            return Some(auto_close_key(self.reader, key, || {
+                self.read_file(&volume_guid, key, &next_subkey)
+            }));
+        }
+    }
+}
+
+impl<'a, R: RegistryReader> Iterator for LegacyFileEntryIter<'a, R> {
+    type Item = LegacyFileEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        next_valid_record(self, "File")
+    }
+}
+
+impl<'a, R: RegistryReader> Drop for LegacyFileEntryIter<'a, R> {
+    fn drop(&mut self) {
+        self.close_volume();
+        self.reader.close_key(self.key);
+        self.key = RegHiveKey::Hkey(0);
+    }
+}
@@ -0,0 +1,45 @@
+use forensic_rs::utils::time::Filetime;
+
+use super::schema::{amcache_record, SchemaIter};
+
+amcache_record! {
+    /// Program installed in Windows 8, Windows Server 2012 and Windows 10 before 1709. Stored in `Root\Programs\{ProgramId}` with numbered values.
+    ///
+    /// https://www.ssi.gouv.fr/uploads/2019/01/anssi-coriin_2019-analysis_amcache.pdf
+    pub struct LegacyProgramEntry in "Programs" {
+        /// Identifier of the program: name of the subkey. Referenced by the value 100 of the files
+        pub program_id: String,
+        /// 0: Name of the program
+        pub name: String = String("0"),
+        /// 1: Version of the program
+        pub version: String = String("1"),
+        /// 2: Publisher of the program
+        pub publisher: String = String("2"),
+        /// 3: LanguageCode
+        pub language_code: u32 = U32("3"),
+        /// 6: Entry type. Ex: AddRemoveProgram
+        pub entry_type: String = String("6"),
+        /// 7: Uninstall registry keys
+        pub uninstall_keys: Vec<String> = StringList("7"),
+        /// a: Installation time
+        #[cfg_attr(feature = "serde", serde(default, with = "crate::common::time::iso8601_option"))]
+        pub install_date: Option<Filetime> = UnixTime("a"),
+        /// d: Installation folders
+        pub paths: Vec<String> = StringList("d"),
+        /// f: Product code
+        pub product_code: String = String("f"),
+        /// 11: MSI product code
+        pub msi_product_code: String = String("11"),
+        /// 12: MSI package code
+        pub msi_package_code: String = String("12"),
+        /// Files: files of the program in the format `{VolumeGuid}@{FileReference}`
+        pub files: Vec<String> = StringList("Files"),
+    }
+
+    fn complete(&mut self) -> ForensicResult<()> {
+        self.program_id = self.key_name.clone();
+        Ok(())
+    }
+}
+
+pub type LegacyProgramEntryIter<'a, R> = SchemaIter<'a, R, LegacyProgramEntry>;
@@ -0,0 +1,24 @@
+use super::schema::{amcache_record, SchemaIter};
+
+amcache_record! {
+    /// This event provides basic information about the memory slots present on the device.
+    ///
+    /// https://learn.microsoft.com/en-us/windows/privacy/required-diagnostic-events-fields-windows-11-22H2#microsoftwindowsinventoryindicatorsinventorymiscellaneousmemoryslotarrayinfoadd
+    pub struct InventoryMiscellaneousMemorySlotArrayInfo in "InventoryMiscellaneousMemorySlotArrayInfo" {
+        /// Capacity: memory size in bytes
+        pub capacity: u64 = U64("Capacity"),
+        /// Manufacturer: name of the DRAM manufacturer
+        pub manufacturer: String = String("Manufacturer"),
+        /// Model: model and sub-model of the memory
+        pub model: String = String("Model"),
+        /// Slot: slot the DRAM is plugged into the motherboard
+        pub slot: String = String("Slot"),
+        /// Speed: configured memory slot speed in MHz
+        pub speed: u32 = U32("Speed"),
+        /// Type: DDR type as defined in the SMBIOS standard
+        pub memory_type: u32 = U32("Type"),
+    }
+}
+
+pub type InventoryMiscellaneousMemorySlotArrayInfoIter<'a, R> =
+    SchemaIter<'a, R, InventoryMiscellaneousMemorySlotArrayInfo>;
@@ -0,0 +1,22 @@
+use super::schema::{amcache_record, SchemaIter};
+
+amcache_record! {
+    /// This event provides data on Unified Update Platform (UUP) products and what version they are at. A change of version reveals an upgrade of the system.
+    ///
+    /// https://learn.microsoft.com/en-us/windows/privacy/required-diagnostic-events-fields-windows-11-22H2#microsoftwindowsinventoryindicatorsinventorymiscellaneousuupinfoadd
+    pub struct InventoryMiscellaneousUUPInfo in "InventoryMiscellaneousUUPInfo" {
+        /// Identifier: UUP identifier
+        pub identifier: String = String("Identifier"),
+        /// LastActivatedVersion: last version of the product that was activated
+        pub last_activated_version: String = String("LastActivatedVersion"),
+        /// PreviousVersion: previous version of the product
+        pub previous_version: String = String("PreviousVersion"),
+        /// Source: UUP source
+        pub source: String = String("Source"),
+        /// Version: current version of the product
+        pub version: String = String("Version"),
+    }
+}
+
+pub type InventoryMiscellaneousUUPInfoIter<'a, R> =
+    SchemaIter<'a, R, InventoryMiscellaneousUUPInfo>;
@@ -1,31 +1,67 @@
-use forensic_rs::prelude::{RegHiveKey, RegValue, RegistryReader};
+use forensic_rs::{info, prelude::ForensicResult};
 
 pub mod app_shortcut;
 pub mod app_file;
+pub mod app_framework;
 pub mod app;
+pub mod app_appv;
+pub mod app_driver;
 pub mod dev_container;
+pub mod dev_interface;
+pub mod dev_media_class;
+pub mod dev_pnp;
+pub mod dev_usbhub;
 pub mod drv_binary;
 pub mod drv_package;
+pub mod drv_package_ext;
+pub mod format;
+pub mod legacy_file;
+pub mod legacy_program;
+pub mod misc_memory_slot;
+pub mod misc_uup;
+pub mod office;
+pub mod schema;
+pub mod census;
+pub mod hash;
+pub mod time;
+pub mod values;
 
-pub(crate) fn empty_string<E>(_ : E) -> RegValue {
-    RegValue::SZ(String::default())
-}
+/// Iterator over the subkeys of an AmCache key that reports the errors found while building each record.
+pub trait RecordIter: Sized {
+    type Record;
+    /// Returns the next record or the error found building it. None when there are no more subkeys.
+    fn next_record(&mut self) -> Option<ForensicResult<Self::Record>>;
 
-pub(crate) fn empty_u32<E>(_ : E) -> RegValue {
-    RegValue::DWord(0)
+    /// Iterates over the result of parsing each subkey instead of skipping the invalid ones.
+    fn results(self) -> RecordResultIter<Self> {
+        RecordResultIter { iter : self }
+    }
 }
-pub(crate) fn empty_u64<E>(_ : E) -> RegValue {
-    RegValue::QWord(0)
+
+/// Yields a ForensicResult for each subkey. Created with `RecordIter::results`.
+pub struct RecordResultIter<I : RecordIter> {
+    iter : I
 }
 
-pub(crate) fn read_value_string_or_empty(reader : &dyn RegistryReader, key : RegHiveKey, name : &str) -> String {
-    reader.read_value(key, name).unwrap_or_else(empty_string).try_into().unwrap_or_default()
+impl<I : RecordIter> Iterator for RecordResultIter<I> {
+    type Item = ForensicResult<I::Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next_record()
+    }
 }
 
-pub(crate) fn read_value_u32_or_empty(reader : &dyn RegistryReader, key : RegHiveKey, name : &str) -> u32 {
-    reader.read_value(key, name).unwrap_or_else(empty_u32).try_into().unwrap_or_default()
+/// Returns the next valid record, skipping the subkeys that cannot be parsed.
+pub(crate) fn next_valid_record<I : RecordIter>(iter : &mut I, name : &str) -> Option<I::Record> {
+    loop {
+        match iter.next_record()? {
+            Ok(v) => return Some(v),
+            Err(e) => info!("Error getting AmCache {} {}", name, e)
+        }
+    }
 }
 
-pub(crate) fn read_value_u64_or_empty(reader : &dyn RegistryReader, key : RegHiveKey, name : &str) -> u64 {
-    reader.read_value(key, name).unwrap_or_else(empty_u64).try_into().unwrap_or_default()
-}
\ No newline at end of file
+/// Path of the subkey of a record relative to the root of the AmCache: `Root\<key>\<subkey>`
+pub(crate) fn record_path(key : &str, subkey : &str) -> String {
+    format!(r"Root\{}\{}", key, subkey)
+}
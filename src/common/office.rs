@@ -0,0 +1,244 @@
+//! Microsoft Office inventory: add-ins, VBA macro usage, products and security settings. Stored in the InventoryMiscellaneousOffice* keys.
+//!
+//! https://learn.microsoft.com/en-us/previous-versions/windows/it-pro/privacy/basic-level-windows-diagnostic-events-and-fields-1803#office-events
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::schema::amcache_record;
+
+/// All the Office records of the AmCache. Keys that do not exist in the hive are left empty.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Office {
+    pub add_ins: Vec<OfficeAddIn>,
+    pub add_in_usage: Vec<OfficeAddInUsage>,
+    pub identifiers: Vec<OfficeIdentifiers>,
+    pub ie_settings: Vec<OfficeIESettings>,
+    pub insights: Vec<OfficeInsights>,
+    pub products: Vec<OfficeProducts>,
+    pub settings: Vec<OfficeSettings>,
+    pub vba: Vec<OfficeVba>,
+    pub vba_rule_violations: Vec<OfficeVbaRuleViolations>,
+}
+
+amcache_record! {
+    /// Office add-in (COM add-ins, VSTO...) registered for an Office application. Malicious add-ins are a persistence mechanism.
+    pub struct OfficeAddIn in "InventoryMiscellaneousOfficeAddIn" {
+        /// AddinCLSID
+        pub addin_clsid: String = String("AddinCLSID"),
+        /// AddInId
+        pub add_in_id: String = String("AddInId"),
+        /// AddinType: COM, VSTO...
+        pub addin_type: String = String("AddinType"),
+        /// BinFileTimestamp
+        pub bin_file_timestamp: String = String("BinFileTimestamp"),
+        /// BinFileVersion
+        pub bin_file_version: String = String("BinFileVersion"),
+        /// Description
+        pub description: String = String("Description"),
+        /// FileId: hash of the add-in binary
+        pub file_id: String = String("FileId"),
+        /// FileSize
+        pub file_size: u64 = U64("FileSize"),
+        /// FriendlyName
+        pub friendly_name: String = String("FriendlyName"),
+        /// FullPath: path of the add-in binary
+        pub full_path: String = String("FullPath"),
+        /// LoadBehavior: 3 loads at startup
+        pub load_behavior: u32 = U32("LoadBehavior"),
+        /// LoadTime: average load time in milliseconds
+        pub load_time: u32 = U32("LoadTime"),
+        /// OfficeApplication: Excel, Word, Outlook...
+        pub office_application: String = String("OfficeApplication"),
+        /// OfficeArchitecture
+        pub office_architecture: String = String("OfficeArchitecture"),
+        /// OfficeVersion
+        pub office_version: String = String("OfficeVersion"),
+        /// OutlookCrashingAddin
+        pub outlook_crashing_addin: u32 = U32("OutlookCrashingAddin"),
+        /// ProductCompany
+        pub product_company: String = String("ProductCompany"),
+        /// ProductName
+        pub product_name: String = String("ProductName"),
+        /// ProductVersion
+        pub product_version: String = String("ProductVersion"),
+        /// ProgramId
+        pub program_id: String = String("ProgramId"),
+        /// Provider
+        pub provider: String = String("Provider"),
+    }
+}
+
+amcache_record! {
+    /// Usage of an Office add-in
+    pub struct OfficeAddInUsage in "InventoryMiscellaneousOfficeAddInUsage" {
+        /// AddInId
+        pub add_in_id: String = String("AddInId"),
+        /// AddinConnectFlagCurrent: current load state of the add-in
+        pub addin_connect_flag_current: u32 = U32("AddinConnectFlagCurrent"),
+        /// AddinConnectFlagLast7: load state of the add-in during the last 7 days
+        pub addin_connect_flag_last7: u32 = U32("AddinConnectFlagLast7"),
+        /// AddinDisabled
+        pub addin_disabled: u32 = U32("AddinDisabled"),
+        /// OfficeApplication
+        pub office_application: String = String("OfficeApplication"),
+    }
+}
+
+amcache_record! {
+    /// Identifiers of the Office installation
+    pub struct OfficeIdentifiers in "InventoryMiscellaneousOfficeIdentifiers" {
+        /// OAudienceData: Office update channel data
+        pub o_audience_data: String = String("OAudienceData"),
+        /// OAudienceId: Office update channel
+        pub o_audience_id: String = String("OAudienceId"),
+        /// OMID: Office machine ID
+        pub omid: String = String("OMID"),
+        /// OPlatform: 32 or 64 bits
+        pub o_platform: String = String("OPlatform"),
+        /// OTenantId: Office 365 tenant
+        pub o_tenant_id: String = String("OTenantId"),
+        /// OVersion
+        pub o_version: String = String("OVersion"),
+        /// OWowMID: Office machine ID of the 32 bits installation in 64 bits Windows
+        pub o_wow_mid: String = String("OWowMID"),
+    }
+}
+
+amcache_record! {
+    /// Internet Explorer feature controls applied to Office. A value of 0 means the security feature is not enforced.
+    pub struct OfficeIESettings in "InventoryMiscellaneousOfficeIESettings" {
+        /// OIeFeatureAddon
+        pub o_ie_feature_addon: u32 = U32("OIeFeatureAddon"),
+        /// OIeMachineLockdown
+        pub o_ie_machine_lockdown: u32 = U32("OIeMachineLockdown"),
+        /// OIeMimeHandling
+        pub o_ie_mime_handling: u32 = U32("OIeMimeHandling"),
+        /// OIeMimeSniffing
+        pub o_ie_mime_sniffing: u32 = U32("OIeMimeSniffing"),
+        /// OIeNoAxInstall: ActiveX installation blocked
+        pub o_ie_no_ax_install: u32 = U32("OIeNoAxInstall"),
+        /// OIeNoDownload
+        pub o_ie_no_download: u32 = U32("OIeNoDownload"),
+        /// OIeObjectCaching
+        pub o_ie_object_caching: u32 = U32("OIeObjectCaching"),
+        /// OIePasswordDisable
+        pub o_ie_password_disable: u32 = U32("OIePasswordDisable"),
+        /// OIeSafeBind
+        pub o_ie_safe_bind: u32 = U32("OIeSafeBind"),
+        /// OIeSecurityBand
+        pub o_ie_security_band: u32 = U32("OIeSecurityBand"),
+        /// OIeUncSaveCheck
+        pub o_ie_unc_save_check: u32 = U32("OIeUncSaveCheck"),
+        /// OIeValidateUrl
+        pub o_ie_validate_url: u32 = U32("OIeValidateUrl"),
+        /// OIeWebOcPopup
+        pub o_ie_web_oc_popup: u32 = U32("OIeWebOcPopup"),
+        /// OIeWinRestrict
+        pub o_ie_win_restrict: u32 = U32("OIeWinRestrict"),
+        /// OIeZoneElevate
+        pub o_ie_zone_elevate: u32 = U32("OIeZoneElevate"),
+    }
+}
+
+amcache_record! {
+    /// Office Insights: issues detected in Office applications
+    pub struct OfficeInsights in "InventoryMiscellaneousOfficeInsights" {
+        /// OfficeApplication
+        pub office_application: String = String("OfficeApplication"),
+        /// OfficeInsightId
+        pub office_insight_id: String = String("OfficeInsightId"),
+        /// OfficeVersion
+        pub office_version: String = String("OfficeVersion"),
+    }
+}
+
+amcache_record! {
+    /// Installed Office products
+    pub struct OfficeProducts in "InventoryMiscellaneousOfficeProducts" {
+        /// OC2rApps: Click-To-Run applications
+        pub oc2r_apps: String = String("OC2rApps"),
+        /// OC2rSkus: Click-To-Run products
+        pub oc2r_skus: String = String("OC2rSkus"),
+        /// OMsiApps: MSI applications
+        pub o_msi_apps: String = String("OMsiApps"),
+        /// OProductCodes: MSI product codes
+        pub o_product_codes: String = String("OProductCodes"),
+        /// State
+        pub state: u32 = U32("State"),
+    }
+}
+
+amcache_record! {
+    /// Office settings
+    pub struct OfficeSettings in "InventoryMiscellaneousOfficeSettings" {
+        /// BrowserFlags
+        pub browser_flags: String = String("BrowserFlags"),
+        /// ExchangeProviderFlags
+        pub exchange_provider_flags: String = String("ExchangeProviderFlags"),
+        /// SharedComputerLicensing
+        pub shared_computer_licensing: u32 = U32("SharedComputerLicensing"),
+    }
+}
+
+amcache_record! {
+    /// Summary of the Office documents with VBA macros. Each counter has a `_x64` twin for the 64 bits compatibility analysis.
+    pub struct OfficeVba in "InventoryMiscellaneousOfficeVBA" {
+        /// Design: documents with VBA code
+        pub design: u32 = U32("Design"),
+        /// Design_x64
+        pub design_x64: u32 = U32("Design_x64"),
+        /// DesignRuleViolations
+        pub design_rule_violations: u32 = U32("DesignRuleViolations"),
+        /// DesignRuleViolations_x64
+        pub design_rule_violations_x64: u32 = U32("DesignRuleViolations_x64"),
+        /// Issues
+        pub issues: u32 = U32("Issues"),
+        /// Issues_x64
+        pub issues_x64: u32 = U32("Issues_x64"),
+        /// IssuesNone
+        pub issues_none: u32 = U32("IssuesNone"),
+        /// IssuesNone_x64
+        pub issues_none_x64: u32 = U32("IssuesNone_x64"),
+        /// Locked: documents with locked VBA projects
+        pub locked: u32 = U32("Locked"),
+        /// Locked_x64
+        pub locked_x64: u32 = U32("Locked_x64"),
+        /// NoVba: documents without VBA
+        pub no_vba: u32 = U32("NoVba"),
+        /// NoVba_x64
+        pub no_vba_x64: u32 = U32("NoVba_x64"),
+        /// Protected: password protected documents
+        pub protected: u32 = U32("Protected"),
+        /// Protected_x64
+        pub protected_x64: u32 = U32("Protected_x64"),
+        /// Score
+        pub score: u32 = U32("Score"),
+        /// Score_x64
+        pub score_x64: u32 = U32("Score_x64"),
+        /// Total: documents analyzed
+        pub total: u32 = U32("Total"),
+        /// Total_x64
+        pub total_x64: u32 = U32("Total_x64"),
+        /// Validation
+        pub validation: u32 = U32("Validation"),
+        /// Validation_x64
+        pub validation_x64: u32 = U32("Validation_x64"),
+    }
+}
+
+amcache_record! {
+    /// Number of times a VBA rule was violated by the macros of the Office documents
+    pub struct OfficeVbaRuleViolations in "InventoryMiscellaneousOfficeVBARuleViolations" {
+        /// Rule identifier: name of the subkey
+        pub rule_id: String,
+        /// Count
+        pub count: u32 = U32("Count"),
+    }
+
+    fn complete(&mut self) -> ForensicResult<()> {
+        self.rule_id = self.key_name.clone();
+        Ok(())
+    }
+}
@@ -0,0 +1,604 @@
+use std::{borrow::Cow, collections::BTreeMap, marker::PhantomData};
+
+use forensic_rs::{
+    err::ForensicResult,
+    traits::registry::{auto_close_key, RegHiveKey, RegValue, RegistryReader},
+    utils::time::Filetime,
+};
+
+#[cfg(any(feature = "json", feature = "toml"))]
+use forensic_rs::err::ForensicError;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{
+    app::InventoryApplication,
+    app_appv::InventoryApplicationAppV,
+    app_driver::InventoryApplicationDriver,
+    app_file::InventoryApplicationFile,
+    app_framework::InventoryApplicationFramework,
+    app_shortcut::InventoryApplicationShortcut,
+    dev_container::InventoryDeviceContainer,
+    dev_interface::InventoryDeviceInterface,
+    dev_media_class::InventoryDeviceMediaClass,
+    dev_pnp::InventoryDevicePnp,
+    dev_usbhub::InventoryDeviceUsbHubClass,
+    drv_binary::InventoryDriverBinary,
+    drv_package::InventoryDriverPackage,
+    drv_package_ext::DriverPackageExtended,
+    legacy_program::LegacyProgramEntry,
+    misc_memory_slot::InventoryMiscellaneousMemorySlotArrayInfo,
+    misc_uup::InventoryMiscellaneousUUPInfo,
+    next_valid_record,
+    office::{
+        OfficeAddIn, OfficeAddInUsage, OfficeIESettings, OfficeIdentifiers, OfficeInsights,
+        OfficeProducts, OfficeSettings, OfficeVba, OfficeVbaRuleViolations,
+    },
+    record_path,
+    values::{FieldAnomaly, ParseOptions, ValueReader},
+    RecordIter,
+};
+
+/// Declares the record of a key known by the crate from a single table: the doc, the type and the registry value of each field.
+///
+/// Generates the struct, with the name and path of the subkey first and the timestamp, anomalies, slack and extra values last, and implements `Record` and `BuiltinRecord` for it. Each field is followed by how it is read:
+///
+/// * `Type("Value", "Alias"...)`: registry value read as `FieldType::Type`. The aliases are used in order when the value does not exist
+/// * `DateTime(field)`: parsed time of the Date field `field`
+/// * `OtherValues(Type)`: the values not described by a field, read as `FieldType::Type`
+/// * Nothing: default value, filled by `BuiltinRecord::complete`
+macro_rules! amcache_record {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident in $key:literal {
+            $(
+                $(#[$field_meta:meta])*
+                pub $field:ident : $ty:ty $(= $kind:ident($($arg:tt)*))?
+            ),* $(,)?
+        }
+        $(fn complete(&mut $self:ident) -> ForensicResult<()> $complete:block)?
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, Default)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub struct $name {
+            /// Name of the subkey
+            pub key_name: String,
+            #[doc = concat!("Path of the subkey from the root of the hive. Starts with `Root\\", $key, "\\`")]
+            pub key_path: String,
+            $(
+                $(#[$field_meta])*
+                pub $field: $ty,
+            )*
+            /// Last write timestamp
+            #[cfg_attr(feature = "serde", serde(with = "crate::common::time::iso8601"))]
+            pub timestamp: forensic_rs::utils::time::Filetime,
+            /// Values that could not be mapped to their field
+            #[cfg_attr(feature = "serde", serde(default))]
+            pub anomalies: Vec<crate::common::values::FieldAnomaly>,
+            /// Data found after the NUL terminator of string values, encoded as UTF-16LE. Only filled if ParseOptions::keep_slack is enabled
+            #[cfg_attr(feature = "serde", serde(default))]
+            pub slack: std::collections::BTreeMap<String, Vec<u8>>,
+            /// Values of the subkey that are not mapped to a field
+            #[cfg_attr(feature = "serde", serde(default, with = "crate::common::values::extra_values"))]
+            pub extra: std::collections::BTreeMap<String, forensic_rs::traits::registry::RegValue>,
+        }
+
+        impl crate::common::schema::Record for $name {
+            fn from_record(
+                record: crate::common::schema::SchemaRecord,
+            ) -> forensic_rs::err::ForensicResult<Self> {
+                let mut this = Self {
+                    $($field: amcache_record!(@read record, $field $(, $kind($($arg)*))?),)*
+                    key_name: record.key_name,
+                    key_path: record.key_path,
+                    timestamp: record.timestamp,
+                    anomalies: record.anomalies,
+                    slack: record.slack,
+                    extra: record.extra,
+                };
+                crate::common::schema::BuiltinRecord::complete(&mut this)?;
+                Ok(this)
+            }
+        }
+
+        impl crate::common::schema::BuiltinRecord for $name {
+            const KEY: &'static str = $key;
+
+            fn schema() -> crate::common::schema::Schema {
+                #[allow(unused_mut)]
+                let mut schema = crate::common::schema::Schema::new($key);
+                $(amcache_record!(@describe schema, $field $(, $kind($($arg)*))?);)*
+                schema
+            }
+
+            $(fn complete(&mut $self) -> forensic_rs::err::ForensicResult<()> $complete)?
+        }
+    };
+    (@describe $schema:ident, $field:ident) => {};
+    (@describe $schema:ident, $field:ident, DateTime($date:ident)) => {};
+    (@describe $schema:ident, $field:ident, OtherValues($kind:ident)) => {
+        $schema.other_values = Some(crate::common::schema::FieldType::$kind);
+    };
+    (@describe $schema:ident, $field:ident, $kind:ident($value:literal $(, $alias:literal)*)) => {
+        $schema.fields.push(
+            crate::common::schema::FieldDescriptor::new(
+                crate::common::schema::field_name(stringify!($field)),
+                $value,
+                crate::common::schema::FieldType::$kind,
+            )$(.alias($alias))*,
+        );
+    };
+    (@read $record:ident, $field:ident) => {
+        Default::default()
+    };
+    (@read $record:ident, $field:ident, DateTime($date:ident)) => {
+        $record.time(crate::common::schema::field_name(stringify!($date)))
+    };
+    (@read $record:ident, $field:ident, OtherValues($kind:ident)) => {
+        $record.other_strings()
+    };
+    (@read $record:ident, $field:ident, StringList($($arg:tt)*)) => {
+        $record.string_list(crate::common::schema::field_name(stringify!($field)))
+    };
+    (@read $record:ident, $field:ident, U32($($arg:tt)*)) => {
+        $record.u32(crate::common::schema::field_name(stringify!($field)))
+    };
+    (@read $record:ident, $field:ident, U64($($arg:tt)*)) => {
+        $record.u64(crate::common::schema::field_name(stringify!($field)))
+    };
+    (@read $record:ident, $field:ident, Filetime($($arg:tt)*)) => {
+        $record.time(crate::common::schema::field_name(stringify!($field)))
+    };
+    (@read $record:ident, $field:ident, UnixTime($($arg:tt)*)) => {
+        $record.time(crate::common::schema::field_name(stringify!($field)))
+    };
+    // String, Text and the raw string of Date
+    (@read $record:ident, $field:ident, $kind:ident($($arg:tt)*)) => {
+        $record.string(crate::common::schema::field_name(stringify!($field)))
+    };
+}
+
+pub(crate) use amcache_record;
+
+/// How the registry value of a field is converted
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "snake_case"))]
+pub enum FieldType {
+    /// REG_SZ or REG_EXPAND_SZ. Empty if missing
+    String,
+    /// Free text: REG_SZ, REG_MULTI_SZ joined by new lines, numbers in decimal and REG_BINARY in hexadecimal
+    Text,
+    /// REG_MULTI_SZ or REG_SZ as a single element list
+    StringList,
+    /// REG_DWORD, REG_QWORD or little endian binary truncated to 32 bits
+    U32,
+    /// REG_DWORD, REG_QWORD or little endian binary
+    U64,
+    /// Date string in one of the formats of `parse_date`, like `11/18/2019 09:08:43` or `2019-11-18`, kept along its parsed value
+    Date,
+    /// FILETIME stored as REG_QWORD or binary
+    Filetime,
+    /// Seconds since 1970 stored as REG_DWORD, REG_QWORD or binary
+    UnixTime,
+}
+
+/// Describes how a field of a record is read from the values of a subkey
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FieldDescriptor {
+    /// Name of the field in the record
+    pub name: String,
+    /// Name of the registry value
+    pub value: String,
+    /// Other names of the value used by some Windows versions. Used in order when `value` does not exist. Ex: ProductVersion and BinProductVersion
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub aliases: Vec<String>,
+    /// Conversion of the registry value
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub field_type: FieldType,
+}
+
+impl FieldDescriptor {
+    pub fn new(name: &str, value: &str, field_type: FieldType) -> Self {
+        Self {
+            name: name.to_string(),
+            value: value.to_string(),
+            aliases: Vec::new(),
+            field_type,
+        }
+    }
+
+    /// Adds a value name to try when the previous ones do not exist
+    pub fn alias(mut self, value: &str) -> Self {
+        self.aliases.push(value.to_string());
+        self
+    }
+
+    fn read(&self, values: &mut ValueReader) -> ForensicResult<FieldValue> {
+        let name = match self.aliases.is_empty() {
+            true => &self.value,
+            false => std::iter::once(&self.value)
+                .chain(self.aliases.iter())
+                .find(|name| values.contains(name))
+                .unwrap_or(&self.value),
+        };
+        Ok(match self.field_type {
+            FieldType::String => FieldValue::String(values.string(name)?),
+            FieldType::Text => FieldValue::String(values.text_opt(name)?.unwrap_or_default()),
+            FieldType::StringList => FieldValue::StringList(values.string_list(name)?),
+            FieldType::U32 => FieldValue::U32(values.u32(name)?),
+            FieldType::U64 => FieldValue::U64(values.u64(name)?),
+            FieldType::Date => {
+                let (raw, time) = values.date(name)?;
+                FieldValue::Date { raw, time }
+            }
+            FieldType::Filetime => FieldValue::Time {
+                time: values.filetime_opt(name)?,
+            },
+            FieldType::UnixTime => FieldValue::Time {
+                time: values.unix_time_opt(name)?,
+            },
+        })
+    }
+}
+
+/// Name of a record field declared with `amcache_record`, without the raw identifier prefix of fields like `r#type`
+pub(crate) fn field_name(field: &'static str) -> &'static str {
+    field.trim_start_matches("r#")
+}
+
+/// Describes the records stored in the subkeys of `Root\<key>`. Allows parsing keys not known by this crate without new code.
+///
+/// ```toml
+/// key = "InventoryApplicationFile"
+///
+/// [[fields]]
+/// name = "product_version"
+/// value = "ProductVersion"
+/// aliases = ["BinProductVersion"]
+/// type = "string"
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Schema {
+    /// Name of the key inside Root. Ex: InventoryApplicationFile
+    pub key: String,
+    /// Fields of every record
+    pub fields: Vec<FieldDescriptor>,
+    /// Type used to read the values that are not described by a field into `SchemaRecord::other`. They are kept in `SchemaRecord::extra` if None
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub other_values: Option<FieldType>,
+}
+
+/// Name of a key and the function returning its schema
+type BuiltinSchema = (&'static str, fn() -> Schema);
+
+/// Keys read with a built-in schema: the keys parsed by the crate whose subkeys are the records. `Root\File` stores its records one level deeper, in a subkey per volume.
+const BUILTIN_SCHEMAS: [BuiltinSchema; 26] = [
+    (DriverPackageExtended::KEY, DriverPackageExtended::schema),
+    (LegacyProgramEntry::KEY, LegacyProgramEntry::schema),
+    (InventoryApplication::KEY, InventoryApplication::schema),
+    (InventoryApplicationAppV::KEY, InventoryApplicationAppV::schema),
+    (InventoryApplicationDriver::KEY, InventoryApplicationDriver::schema),
+    (InventoryApplicationFile::KEY, InventoryApplicationFile::schema),
+    (InventoryApplicationFramework::KEY, InventoryApplicationFramework::schema),
+    (InventoryApplicationShortcut::KEY, InventoryApplicationShortcut::schema),
+    (InventoryDeviceContainer::KEY, InventoryDeviceContainer::schema),
+    (InventoryDeviceInterface::KEY, InventoryDeviceInterface::schema),
+    (InventoryDeviceMediaClass::KEY, InventoryDeviceMediaClass::schema),
+    (InventoryDevicePnp::KEY, InventoryDevicePnp::schema),
+    (InventoryDeviceUsbHubClass::KEY, InventoryDeviceUsbHubClass::schema),
+    (InventoryDriverBinary::KEY, InventoryDriverBinary::schema),
+    (InventoryDriverPackage::KEY, InventoryDriverPackage::schema),
+    (InventoryMiscellaneousMemorySlotArrayInfo::KEY, InventoryMiscellaneousMemorySlotArrayInfo::schema),
+    (OfficeAddIn::KEY, OfficeAddIn::schema),
+    (OfficeAddInUsage::KEY, OfficeAddInUsage::schema),
+    (OfficeIdentifiers::KEY, OfficeIdentifiers::schema),
+    (OfficeIESettings::KEY, OfficeIESettings::schema),
+    (OfficeInsights::KEY, OfficeInsights::schema),
+    (OfficeProducts::KEY, OfficeProducts::schema),
+    (OfficeSettings::KEY, OfficeSettings::schema),
+    (OfficeVba::KEY, OfficeVba::schema),
+    (OfficeVbaRuleViolations::KEY, OfficeVbaRuleViolations::schema),
+    (InventoryMiscellaneousUUPInfo::KEY, InventoryMiscellaneousUUPInfo::schema),
+];
+
+impl Schema {
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a field to the schema
+    pub fn field(mut self, field: FieldDescriptor) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    /// Schema used by the crate to read a key. None if the key is not in `Schema::builtin_keys`
+    pub fn builtin(key: &str) -> Option<Self> {
+        BUILTIN_SCHEMAS
+            .iter()
+            .find(|(name, _)| *name == key)
+            .map(|(_, schema)| schema())
+    }
+
+    /// Keys with a built-in schema
+    pub fn builtin_keys() -> impl Iterator<Item = &'static str> {
+        BUILTIN_SCHEMAS.iter().map(|(name, _)| *name)
+    }
+
+    /// Loads a schema from a JSON document
+    #[cfg(feature = "json")]
+    pub fn from_json(data: &str) -> ForensicResult<Self> {
+        serde_json::from_str(data)
+            .map_err(|e| ForensicError::bad_format_string(format!("Invalid JSON schema: {}", e)))
+    }
+
+    /// Loads a schema from a TOML document
+    #[cfg(feature = "toml")]
+    pub fn from_toml(data: &str) -> ForensicResult<Self> {
+        toml::from_str(data)
+            .map_err(|e| ForensicError::bad_format_string(format!("Invalid TOML schema: {}", e)))
+    }
+
+    /// Reads the fields of a record from the values of its subkey
+    pub(crate) fn read_record(
+        &self,
+        reader: &dyn RegistryReader,
+        key: RegHiveKey,
+        key_name: &str,
+        key_path: String,
+        options: ParseOptions,
+    ) -> ForensicResult<SchemaRecord> {
+        let mut values = ValueReader::new(reader, key, options);
+        let mut fields = BTreeMap::new();
+        for field in &self.fields {
+            fields.insert(field.name.clone(), field.read(&mut values)?);
+        }
+        let mut other = BTreeMap::new();
+        if let Some(field_type) = self.other_values {
+            for name in values.value_names()? {
+                if !values.was_read(&name) {
+                    let value = FieldDescriptor::new(&name, &name, field_type).read(&mut values)?;
+                    other.insert(name, value);
+                }
+            }
+        }
+
+        let key_info = reader.key_info(key)?;
+        let (anomalies, slack, extra) = values.finish();
+        Ok(SchemaRecord {
+            key_name: key_name.to_string(),
+            key_path,
+            fields,
+            other,
+            timestamp: key_info.last_write_time,
+            anomalies,
+            slack,
+            extra,
+        })
+    }
+}
+
+/// Value of a field read with a FieldDescriptor
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(tag = "type", content = "data", rename_all = "snake_case"))]
+pub enum FieldValue {
+    /// FieldType::String and FieldType::Text
+    String(String),
+    /// FieldType::StringList
+    StringList(Vec<String>),
+    /// FieldType::U32
+    U32(u32),
+    /// FieldType::U64
+    U64(u64),
+    /// FieldType::Date: the date string and its value parsed as UTC
+    Date {
+        raw: String,
+        #[cfg_attr(feature = "serde", serde(with = "crate::common::time::iso8601_option"))]
+        time: Option<Filetime>,
+    },
+    /// FieldType::Filetime and FieldType::UnixTime
+    Time {
+        #[cfg_attr(feature = "serde", serde(with = "crate::common::time::iso8601_option"))]
+        time: Option<Filetime>,
+    },
+}
+
+impl FieldValue {
+    /// Content of String fields and raw string of Date fields
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            FieldValue::String(v) => Some(v),
+            FieldValue::Date { raw, .. } => Some(raw),
+            _ => None,
+        }
+    }
+
+    /// Value of U32 and U64 fields
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            FieldValue::U32(v) => Some(*v as u64),
+            FieldValue::U64(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Parsed time of Date, Filetime and UnixTime fields
+    pub fn as_filetime(&self) -> Option<Filetime> {
+        match self {
+            FieldValue::Date { time, .. } | FieldValue::Time { time } => *time,
+            _ => None,
+        }
+    }
+}
+
+/// Record read with a Schema
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SchemaRecord {
+    /// Name of the subkey
+    pub key_name: String,
+    /// Path of the subkey: `Root\<Schema::key>\<key_name>`
+    pub key_path: String,
+    /// Fields of the schema by name
+    pub fields: BTreeMap<String, FieldValue>,
+    /// Values not described by a field by value name. Only filled if Schema::other_values is set
+    pub other: BTreeMap<String, FieldValue>,
+    /// Last write timestamp
+    #[cfg_attr(feature = "serde", serde(with = "crate::common::time::iso8601"))]
+    pub timestamp: Filetime,
+    /// Values that could not be mapped to their field
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub anomalies: Vec<FieldAnomaly>,
+    /// Data found after the NUL terminator of string values, encoded as UTF-16LE. Only filled if ParseOptions::keep_slack is enabled
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub slack: BTreeMap<String, Vec<u8>>,
+    /// Values of the subkey that are not mapped to a field
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::common::values::extra_values"))]
+    pub extra: BTreeMap<String, RegValue>,
+}
+
+impl SchemaRecord {
+    /// Value of a field by its name
+    pub fn get(&self, name: &str) -> Option<&FieldValue> {
+        self.fields.get(name)
+    }
+
+    pub(crate) fn string(&self, name: &str) -> String {
+        self.get(name)
+            .and_then(FieldValue::as_str)
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    pub(crate) fn string_list(&self, name: &str) -> Vec<String> {
+        match self.get(name) {
+            Some(FieldValue::StringList(v)) => v.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub(crate) fn u32(&self, name: &str) -> u32 {
+        match self.get(name) {
+            Some(FieldValue::U32(v)) => *v,
+            _ => 0,
+        }
+    }
+
+    pub(crate) fn u64(&self, name: &str) -> u64 {
+        self.get(name)
+            .and_then(FieldValue::as_u64)
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn time(&self, name: &str) -> Option<Filetime> {
+        self.get(name).and_then(FieldValue::as_filetime)
+    }
+
+    /// Other values read as String or Text
+    pub(crate) fn other_strings(&self) -> BTreeMap<String, String> {
+        self.other
+            .iter()
+            .filter_map(|(name, value)| Some((name.clone(), value.as_str()?.to_string())))
+            .collect()
+    }
+}
+
+/// Record built from the fields read with a Schema
+pub trait Record: Sized {
+    fn from_record(record: SchemaRecord) -> ForensicResult<Self>;
+}
+
+impl Record for SchemaRecord {
+    fn from_record(record: SchemaRecord) -> ForensicResult<Self> {
+        Ok(record)
+    }
+}
+
+/// Record of a key parsed by the crate, declared with `amcache_record`
+pub trait BuiltinRecord: Record {
+    /// Name of the key inside Root
+    const KEY: &'static str;
+
+    /// Fields read from the values of each subkey
+    fn schema() -> Schema;
+
+    /// Fills the fields that are not read from a value, like the ones taken from the name of the subkey
+    fn complete(&mut self) -> ForensicResult<()> {
+        Ok(())
+    }
+}
+
+/// Iterates over the subkeys of a key, reading a record from each one with a Schema. Yields SchemaRecord for the schemas given by the user and the typed record for the keys parsed by the crate.
+pub struct SchemaIter<'a, R: RegistryReader, T: Record = SchemaRecord> {
+    pos: u32,
+    key: RegHiveKey,
+    reader: &'a R,
+    options: ParseOptions,
+    schema: Cow<'a, Schema>,
+    record: PhantomData<T>,
+}
+
+impl<'a, R: RegistryReader, T: Record> SchemaIter<'a, R, T> {
+    pub(crate) fn new(
+        reader: &'a R,
+        key: RegHiveKey,
+        options: ParseOptions,
+        schema: Cow<'a, Schema>,
+    ) -> Self {
+        Self {
+            pos: 0,
+            key,
+            reader,
+            options,
+            schema,
+            record: PhantomData,
+        }
+    }
+}
+
+impl<'a, R: RegistryReader, T: Record> RecordIter for SchemaIter<'a, R, T> {
+    type Record = T;
+
+    fn next_record(&mut self) -> Option<ForensicResult<Self::Record>> {
+        if self.key == RegHiveKey::Hkey(0) {
+            return None;
+        }
+        let pos = self.pos;
+        self.pos += 1;
+        let next_subkey = self.reader.key_at(self.key, pos).ok()?;
+        let key = match self.reader.open_key(self.key, &next_subkey) {
+            Ok(v) => v,
+            Err(e) => return Some(Err(e)),
+        };
+        Some(auto_close_key(self.reader, key, || {
+            let key_path = record_path(&self.schema.key, &next_subkey);
+            let record =
+                self.schema
+                    .read_record(self.reader, key, &next_subkey, key_path, self.options)?;
+            T::from_record(record)
+        }))
+    }
+}
+
+impl<'a, R: RegistryReader, T: Record> Iterator for SchemaIter<'a, R, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.schema.key.clone();
+        next_valid_record(self, &key)
+    }
+}
+
+impl<'a, R: RegistryReader, T: Record> Drop for SchemaIter<'a, R, T> {
+    fn drop(&mut self) {
+        self.reader.close_key(self.key);
+        self.key = RegHiveKey::Hkey(0);
+    }
+}
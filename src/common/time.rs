@@ -0,0 +1,186 @@
+use forensic_rs::utils::time::Filetime;
+
+/// Parses the date strings stored by the AmCache. Dates are in UTC and use different formats depending on the value:
+///
+/// * `MM/DD/YYYY HH:MM:SS`: LinkDate, InstallDate, DriverLastWriteTime
+/// * `YYYY-M-D`: Date of InventoryDriverPackage
+/// * `MM-DD-YYYY`: InstallDate of InventoryDevicePnp
+///
+/// ```rust
+/// use frnsc_amcache::common::time::parse_date;
+/// assert_eq!("23-08-2019 05:00:00", format!("{:?}", parse_date("08/23/2019 05:00:00").unwrap()));
+/// assert_eq!("12-04-2019 00:00:00", format!("{:?}", parse_date("2019-4-12").unwrap()));
+/// assert!(parse_date("").is_none());
+/// ```
+pub fn parse_date(value: &str) -> Option<Filetime> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    let (date, time) = match value.split_once(' ') {
+        Some((date, time)) => (date, Some(time.trim())),
+        None => (value, None),
+    };
+    let mut date_parts = date.split(['/', '-', '.']);
+    let first = date_parts.next()?;
+    let second: u8 = date_parts.next()?.parse().ok()?;
+    let third = date_parts.next()?;
+    if date_parts.next().is_some() {
+        return None;
+    }
+    let (year, month, day): (u16, u8, u8) = if first.len() == 4 {
+        (first.parse().ok()?, second, third.parse().ok()?)
+    } else {
+        (third.parse().ok()?, first.parse().ok()?, second)
+    };
+    let (hour, minute, second) = match time {
+        Some(time) => {
+            let mut time_parts = time.split(':');
+            let hour: u8 = time_parts.next()?.parse().ok()?;
+            let minute: u8 = time_parts.next()?.parse().ok()?;
+            let second: u8 = match time_parts.next() {
+                Some(v) => v.parse().ok()?,
+                None => 0,
+            };
+            (hour, minute, second)
+        }
+        None => (0, 0, 0),
+    };
+    if !is_valid_date_time(year, month, day, hour, minute, second) {
+        return None;
+    }
+    Some(Filetime::with_ymd_and_hms(
+        year, month, day, hour, minute, second, 0,
+    ))
+}
+
+/// Number of days of the month in the Gregorian calendar
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Checks the ranges supported by Filetime. Filetime::with_ymd_and_hms rolls over invalid days like 02/31 instead of failing.
+fn is_valid_date_time(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> bool {
+    (1601..=30827).contains(&year)
+        && (1..=12).contains(&month)
+        && day >= 1
+        && day <= days_in_month(year, month)
+        && hour <= 23
+        && minute <= 59
+        && second <= 59
+}
+
+/// Converts the seconds since 1970-01-01 stored by the legacy AmCache layout. None if the timestamp is 0.
+///
+/// ```rust
+/// use frnsc_amcache::common::time::from_unix_seconds;
+/// assert_eq!("14-07-2009 01:14:34", format!("{:?}", from_unix_seconds(1247534074).unwrap()));
+/// assert!(from_unix_seconds(0).is_none());
+/// ```
+pub fn from_unix_seconds(seconds: u64) -> Option<Filetime> {
+    if seconds == 0 {
+        return None;
+    }
+    // Seconds between 1601-01-01 and 1970-01-01
+    let filetime = seconds.checked_add(11_644_473_600)?.checked_mul(10_000_000)?;
+    Some(Filetime::new(filetime))
+}
+
+/// Formats a Filetime as ISO-8601 in UTC with the 100ns precision of the Filetime: `2019-08-23T05:00:00.0000000Z`
+pub fn to_iso8601(time: &Filetime) -> String {
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:07}Z",
+        time.year(),
+        time.month(),
+        time.day(),
+        time.hour(),
+        time.minute(),
+        time.second(),
+        time.nanoseconds() / 100
+    )
+}
+
+/// Parses an ISO-8601 UTC date like the ones generated by `to_iso8601`
+///
+/// ```rust
+/// use frnsc_amcache::common::time::{from_iso8601, to_iso8601};
+/// let time = from_iso8601("2019-09-04T21:19:04.283Z").unwrap();
+/// assert_eq!("2019-09-04T21:19:04.2830000Z", to_iso8601(&time));
+/// assert_eq!(time.filetime(), from_iso8601(&to_iso8601(&time)).unwrap().filetime());
+/// ```
+pub fn from_iso8601(value: &str) -> Option<Filetime> {
+    let value = value.strip_suffix('Z').unwrap_or(value);
+    let (date, time) = value.split_once('T')?;
+    let mut date_parts = date.split('-');
+    let year: u16 = date_parts.next()?.parse().ok()?;
+    let month: u8 = date_parts.next()?.parse().ok()?;
+    let day: u8 = date_parts.next()?.parse().ok()?;
+    let (time, fraction) = match time.split_once('.') {
+        Some((time, fraction)) => (time, fraction),
+        None => (time, ""),
+    };
+    let mut time_parts = time.split(':');
+    let hour: u8 = time_parts.next()?.parse().ok()?;
+    let minute: u8 = time_parts.next()?.parse().ok()?;
+    let second: u8 = time_parts.next()?.parse().ok()?;
+    if fraction.len() > 9 || !fraction.bytes().all(|v| v.is_ascii_digit()) {
+        return None;
+    }
+    let nanos: u32 = format!("{:0<9}", fraction).parse().ok()?;
+    if !is_valid_date_time(year, month, day, hour, minute, second) {
+        return None;
+    }
+    Some(Filetime::with_ymd_and_hms(
+        year, month, day, hour, minute, second, nanos,
+    ))
+}
+
+/// Serializes a Filetime as an ISO-8601 string. Use with `#[serde(with = "frnsc_amcache::common::time::iso8601")]`
+#[cfg(feature = "serde")]
+pub mod iso8601 {
+    use forensic_rs::utils::time::Filetime;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(time: &Filetime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::to_iso8601(time))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Filetime, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        super::from_iso8601(&value)
+            .ok_or_else(|| D::Error::custom(format!("Invalid ISO-8601 date: {}", value)))
+    }
+}
+
+/// Serializes an optional Filetime as an ISO-8601 string or null.
+#[cfg(feature = "serde")]
+pub mod iso8601_option {
+    use forensic_rs::utils::time::Filetime;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        time: &Option<Filetime>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match time {
+            Some(time) => serializer.serialize_some(&super::to_iso8601(time)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Filetime>, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(value) => super::from_iso8601(&value)
+                .map(Some)
+                .ok_or_else(|| D::Error::custom(format!("Invalid ISO-8601 date: {}", value))),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use forensic_rs::{
+    err::{ForensicError, ForensicResult},
+    traits::registry::{RegHiveKey, RegValue, RegistryReader},
+    utils::time::Filetime,
+};
+
+use super::time::{from_unix_seconds, parse_date};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How the parser reacts to registry values that cannot be converted to the expected type.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Invalid values are replaced with a default and reported as a FieldAnomaly of the record.
+    #[default]
+    Lenient,
+    /// Invalid values make the record fail. Missing values are still reported as a FieldAnomaly.
+    Strict,
+}
+
+/// Configures how the values of each record are parsed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    pub mode: ParseMode,
+    /// Keep the data found after the NUL terminator of string values in the `slack` field of each record.
+    ///
+    /// RegistryReader returns strings already decoded from UTF-16LE, so the slack is encoded again from the decoded string: it matches the cell contents except for unpaired surrogates, which the reader replaces with U+FFFD (`FD FF`).
+    pub keep_slack: bool,
+}
+
+/// Why a field of a record does not contain the data stored in the registry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "snake_case"))]
+pub enum AnomalyKind {
+    /// The value does not exist or has no data
+    Missing,
+    /// The value exists but its registry type is not the expected one
+    WrongType,
+    /// The value exists but its content cannot be parsed
+    Corrupt,
+}
+
+/// A registry value that was not mapped to its field as is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FieldAnomaly {
+    /// Name of the registry value
+    pub value: String,
+    pub kind: AnomalyKind,
+}
+
+/// String slack of a record by value name
+pub(crate) type Slack = BTreeMap<String, Vec<u8>>;
+/// Values of a key that are not mapped to a field of the record
+pub(crate) type ExtraValues = BTreeMap<String, RegValue>;
+
+/// Reads the values of a registry key keeping track of the anomalies found.
+pub(crate) struct ValueReader<'a> {
+    reader: &'a dyn RegistryReader,
+    key: RegHiveKey,
+    options: ParseOptions,
+    anomalies: Vec<FieldAnomaly>,
+    slack: BTreeMap<String, Vec<u8>>,
+    /// Lowercase names of the values already read
+    read_names: BTreeSet<String>,
+}
+
+impl<'a> ValueReader<'a> {
+    pub fn new(reader: &'a dyn RegistryReader, key: RegHiveKey, options: ParseOptions) -> Self {
+        Self {
+            reader,
+            key,
+            options,
+            anomalies: Vec::new(),
+            slack: BTreeMap::new(),
+            read_names: BTreeSet::new(),
+        }
+    }
+
+    /// Returns the anomalies, the string slack found and the values of the key that were not read.
+    pub fn finish(self) -> (Vec<FieldAnomaly>, Slack, ExtraValues) {
+        let mut extra = BTreeMap::new();
+        for name in self.value_names().unwrap_or_default() {
+            if self.read_names.contains(&name.to_lowercase()) {
+                continue;
+            }
+            if let Ok(value) = self.reader.read_value(self.key, &name) {
+                extra.insert(name, value);
+            }
+        }
+        (self.anomalies, self.slack, extra)
+    }
+
+    /// Truncates the string at the first NUL character. Registry cells are not zeroed, so the data after the terminator is garbage from previous contents.
+    /// The slack is rebuilt from the decoded string, see ParseOptions::keep_slack.
+    fn truncate(&mut self, name: &str, mut value: String) -> String {
+        if let Some(pos) = value.find('\0') {
+            if self.options.keep_slack {
+                let slack = value[pos + 1..]
+                    .encode_utf16()
+                    .flat_map(|v| v.to_le_bytes())
+                    .collect();
+                self.slack.insert(name.to_string(), slack);
+            }
+            value.truncate(pos);
+        }
+        value
+    }
+
+    /// Registers an anomaly. Returns an error in strict mode if the anomaly is not a missing value.
+    pub(crate) fn anomaly(&mut self, name: &str, kind: AnomalyKind) -> ForensicResult<()> {
+        if self.options.mode == ParseMode::Strict && kind != AnomalyKind::Missing {
+            return Err(ForensicError::bad_format_string(format!(
+                "Invalid value {}: {:?}",
+                name, kind
+            )));
+        }
+        self.anomalies.push(FieldAnomaly {
+            value: name.to_string(),
+            kind,
+        });
+        Ok(())
+    }
+
+    fn read(&mut self, name: &str) -> ForensicResult<Option<RegValue>> {
+        self.read_names.insert(name.to_lowercase());
+        match self.reader.read_value(self.key, name) {
+            Ok(v) => Ok(Some(v)),
+            Err(ForensicError::Missing(_)) => {
+                self.anomaly(name, AnomalyKind::Missing)?;
+                Ok(None)
+            }
+            Err(_) => {
+                self.anomaly(name, AnomalyKind::Corrupt)?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Checks if the value exists without registering an anomaly
+    pub fn contains(&self, name: &str) -> bool {
+        self.reader.read_value(self.key, name).is_ok()
+    }
+
+    /// Checks if the value was already read by one of the fields of the record
+    pub fn was_read(&self, name: &str) -> bool {
+        self.read_names.contains(&name.to_lowercase())
+    }
+
+    pub fn string_opt(&mut self, name: &str) -> ForensicResult<Option<String>> {
+        Ok(match self.read(name)? {
+            Some(RegValue::SZ(v)) | Some(RegValue::ExpandSZ(v)) => Some(self.truncate(name, v)),
+            Some(RegValue::MultiSZ(v)) => Some(v.join("\n")),
+            Some(_) => {
+                self.anomaly(name, AnomalyKind::WrongType)?;
+                None
+            }
+            None => None,
+        })
+    }
+
+    pub fn string(&mut self, name: &str) -> ForensicResult<String> {
+        Ok(self.string_opt(name)?.unwrap_or_default())
+    }
+
+    /// Names of all the values of the key. The value list cell is padded to 8 bytes, so the list is cut at the value count of the key to skip stale entries.
+    pub fn value_names(&self) -> ForensicResult<Vec<String>> {
+        let mut names = self.reader.enumerate_values(self.key)?;
+        if let Ok(info) = self.reader.key_info(self.key) {
+            names.truncate(info.values as usize);
+        }
+        Ok(names)
+    }
+
+    /// Reads a value of any type as text. Numbers are written in decimal and binary data in hexadecimal.
+    pub fn text_opt(&mut self, name: &str) -> ForensicResult<Option<String>> {
+        Ok(match self.read(name)? {
+            Some(RegValue::SZ(v)) | Some(RegValue::ExpandSZ(v)) => Some(self.truncate(name, v)),
+            Some(RegValue::MultiSZ(v)) => Some(v.join("\n")),
+            Some(RegValue::DWord(v)) => Some(v.to_string()),
+            Some(RegValue::QWord(v)) => Some(v.to_string()),
+            Some(RegValue::Binary(v)) => Some(v.iter().map(|v| format!("{:02x}", v)).collect()),
+            None => None,
+        })
+    }
+
+    /// Reads a list of strings stored as REG_MULTI_SZ or as a comma/semicolon separated string.
+    pub fn string_list(&mut self, name: &str) -> ForensicResult<Vec<String>> {
+        Ok(match self.read(name)? {
+            Some(RegValue::MultiSZ(v)) => v.into_iter().filter(|v| !v.is_empty()).collect(),
+            Some(RegValue::SZ(v)) | Some(RegValue::ExpandSZ(v)) => self
+                .truncate(name, v)
+                .split([',', ';'])
+                .map(|v| v.trim())
+                .filter(|v| !v.is_empty())
+                .map(|v| v.to_string())
+                .collect(),
+            Some(_) => {
+                self.anomaly(name, AnomalyKind::WrongType)?;
+                Vec::new()
+            }
+            None => Vec::new(),
+        })
+    }
+
+    /// Reads an integer of `size` bytes. Numeric values are stored as DWORD, QWORD, decimal strings or little-endian REG_BINARY data. REG_BINARY data can be shorter or longer than the number (ex: 12 bytes for a QWORD).
+    fn integer(&mut self, name: &str, size: usize) -> ForensicResult<Option<u64>> {
+        Ok(match self.read(name)? {
+            Some(RegValue::DWord(v)) => Some(v as u64),
+            Some(RegValue::QWord(v)) => Some(v),
+            // Many numeric values are stored as strings
+            Some(RegValue::SZ(v)) => {
+                let v = self.truncate(name, v);
+                if v.is_empty() {
+                    self.anomaly(name, AnomalyKind::Missing)?;
+                    return Ok(None);
+                }
+                match v.parse::<u64>() {
+                    Ok(v) => Some(v),
+                    Err(_) => {
+                        self.anomaly(name, AnomalyKind::Corrupt)?;
+                        None
+                    }
+                }
+            }
+            Some(RegValue::Binary(v)) if !v.is_empty() => {
+                let len = v.len().min(size);
+                let mut buffer = [0u8; 8];
+                buffer[..len].copy_from_slice(&v[..len]);
+                Some(u64::from_le_bytes(buffer))
+            }
+            Some(_) => {
+                self.anomaly(name, AnomalyKind::WrongType)?;
+                None
+            }
+            None => None,
+        })
+    }
+
+    pub fn u64_opt(&mut self, name: &str) -> ForensicResult<Option<u64>> {
+        self.integer(name, 8)
+    }
+
+    pub fn u64(&mut self, name: &str) -> ForensicResult<u64> {
+        Ok(self.u64_opt(name)?.unwrap_or_default())
+    }
+
+    pub fn u32(&mut self, name: &str) -> ForensicResult<u32> {
+        Ok(match self.integer(name, 4)? {
+            Some(v) => match u32::try_from(v) {
+                Ok(v) => v,
+                Err(_) => {
+                    self.anomaly(name, AnomalyKind::Corrupt)?;
+                    0
+                }
+            },
+            None => 0,
+        })
+    }
+
+    /// Reads a FILETIME stored as a number. None if the value is missing or 0.
+    pub fn filetime_opt(&mut self, name: &str) -> ForensicResult<Option<Filetime>> {
+        Ok(self.u64_opt(name)?.filter(|v| *v != 0).map(Filetime::new))
+    }
+
+    /// Reads a number of seconds since 1970-01-01. None if the value is missing or 0.
+    pub fn unix_time_opt(&mut self, name: &str) -> ForensicResult<Option<Filetime>> {
+        Ok(self.u64_opt(name)?.and_then(from_unix_seconds))
+    }
+
+    /// Reads a date string. Returns the raw string and the parsed date.
+    pub fn date(&mut self, name: &str) -> ForensicResult<(String, Option<Filetime>)> {
+        let raw = self.string(name)?;
+        let time = parse_date(&raw);
+        if time.is_none() && !raw.is_empty() {
+            self.anomaly(name, AnomalyKind::Corrupt)?;
+        }
+        Ok((raw, time))
+    }
+}
+
+/// Serializes the values not mapped to a field as a map of `{"type": "REG_SZ", "data": ...}` objects. REG_BINARY data is written in hexadecimal.
+/// Use with `#[serde(with = "frnsc_amcache::common::values::extra_values")]`
+#[cfg(feature = "serde")]
+pub mod extra_values {
+    use std::collections::BTreeMap;
+
+    use forensic_rs::traits::registry::RegValue;
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(tag = "type", content = "data")]
+    enum Value {
+        #[serde(rename = "REG_BINARY")]
+        Binary(String),
+        #[serde(rename = "REG_MULTI_SZ")]
+        MultiSZ(Vec<String>),
+        #[serde(rename = "REG_EXPAND_SZ")]
+        ExpandSZ(String),
+        #[serde(rename = "REG_SZ")]
+        SZ(String),
+        #[serde(rename = "REG_DWORD")]
+        DWord(u32),
+        #[serde(rename = "REG_QWORD")]
+        QWord(u64),
+    }
+
+    impl From<&RegValue> for Value {
+        fn from(value: &RegValue) -> Self {
+            match value {
+                RegValue::Binary(v) => {
+                    Value::Binary(v.iter().map(|v| format!("{:02x}", v)).collect())
+                }
+                RegValue::MultiSZ(v) => Value::MultiSZ(v.clone()),
+                RegValue::ExpandSZ(v) => Value::ExpandSZ(v.clone()),
+                RegValue::SZ(v) => Value::SZ(v.clone()),
+                RegValue::DWord(v) => Value::DWord(*v),
+                RegValue::QWord(v) => Value::QWord(*v),
+            }
+        }
+    }
+
+    impl TryFrom<Value> for RegValue {
+        type Error = String;
+
+        fn try_from(value: Value) -> Result<Self, Self::Error> {
+            Ok(match value {
+                Value::Binary(v) => {
+                    if v.len() % 2 != 0 || !v.is_ascii() {
+                        return Err(format!("Invalid hex string: {}", v));
+                    }
+                    let data = (0..v.len())
+                        .step_by(2)
+                        .map(|i| u8::from_str_radix(&v[i..i + 2], 16))
+                        .collect::<Result<Vec<u8>, _>>()
+                        .map_err(|_| format!("Invalid hex string: {}", v))?;
+                    RegValue::Binary(data)
+                }
+                Value::MultiSZ(v) => RegValue::MultiSZ(v),
+                Value::ExpandSZ(v) => RegValue::ExpandSZ(v),
+                Value::SZ(v) => RegValue::SZ(v),
+                Value::DWord(v) => RegValue::DWord(v),
+                Value::QWord(v) => RegValue::QWord(v),
+            })
+        }
+    }
+
+    pub fn serialize<S: Serializer>(
+        values: &BTreeMap<String, RegValue>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(values.iter().map(|(k, v)| (k, Value::from(v))))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<String, RegValue>, D::Error> {
+        BTreeMap::<String, Value>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(k, v)| Ok((k, RegValue::try_from(v).map_err(D::Error::custom)?)))
+            .collect()
+    }
+}
@@ -0,0 +1,617 @@
+//! CSV export with the same files and column layout as Eric Zimmerman's AmcacheParser.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use forensic_rs::{
+    err::{ForensicError, ForensicResult},
+    traits::registry::RegistryReader,
+    utils::time::Filetime,
+};
+
+use crate::{
+    amcache::AmCache,
+    common::{
+        app::InventoryApplication,
+        app_file::InventoryApplicationFile,
+        app_shortcut::InventoryApplicationShortcut,
+        dev_container::InventoryDeviceContainer,
+        dev_pnp::InventoryDevicePnp,
+        drv_binary::InventoryDriverBinary,
+        drv_package::InventoryDriverPackage,
+        hash::Sha1Hash,
+        time::{from_unix_seconds, parse_date},
+    },
+};
+
+pub const ASSOCIATED_FILE_ENTRIES: &str = "Amcache_AssociatedFileEntries.csv";
+pub const UNASSOCIATED_FILE_ENTRIES: &str = "Amcache_UnassociatedFileEntries.csv";
+pub const PROGRAM_ENTRIES: &str = "Amcache_ProgramEntries.csv";
+pub const SHORTCUTS: &str = "Amcache_ShortCuts.csv";
+pub const DEVICE_CONTAINERS: &str = "Amcache_DeviceContainers.csv";
+pub const DEVICE_PNPS: &str = "Amcache_DevicePnps.csv";
+pub const DRIVER_BINARIES: &str = "Amcache_DriveBinaries.csv";
+pub const DRIVER_PACKAGES: &str = "Amcache_DriverPackages.csv";
+
+const FILE_ENTRY_COLUMNS: [&str; 21] = [
+    "ApplicationName",
+    "ProgramId",
+    "FileKeyLastWriteTimestamp",
+    "SHA1",
+    "IsOsComponent",
+    "FullPath",
+    "Name",
+    "FileExtension",
+    "LinkDate",
+    "ProductName",
+    "Size",
+    "Version",
+    "ProductVersion",
+    "LongPathHash",
+    "BinaryType",
+    "IsPeFile",
+    "BinFileVersion",
+    "BinProductVersion",
+    "Usn",
+    "Language",
+    "Description",
+];
+
+const PROGRAM_ENTRY_COLUMNS: [&str; 22] = [
+    "ProgramId",
+    "KeyLastWriteTimestamp",
+    "Name",
+    "Version",
+    "Publisher",
+    "InstallDate",
+    "OSVersionAtInstallTime",
+    "BundleManifestPath",
+    "HiddenArp",
+    "InboxModernApp",
+    "Language",
+    "ManifestPath",
+    "MsiPackageCode",
+    "MsiProductCode",
+    "PackageFullName",
+    "ProgramInstanceId",
+    "RegistryKeyPath",
+    "RootDirPath",
+    "Source",
+    "StoreAppType",
+    "Type",
+    "UninstallString",
+];
+
+const SHORTCUT_COLUMNS: [&str; 3] = ["KeyName", "LnkName", "KeyLastWriteTimestamp"];
+
+const DEVICE_CONTAINER_COLUMNS: [&str; 17] = [
+    "KeyName",
+    "KeyLastWriteTimestamp",
+    "Categories",
+    "DiscoveryMethod",
+    "FriendlyName",
+    "Icon",
+    "IsActive",
+    "IsConnected",
+    "IsMachineContainer",
+    "IsNetworked",
+    "IsPaired",
+    "Manufacturer",
+    "ModelId",
+    "ModelName",
+    "ModelNumber",
+    "PrimaryCategory",
+    "State",
+];
+
+const DEVICE_PNP_COLUMNS: [&str; 30] = [
+    "KeyName",
+    "KeyLastWriteTimestamp",
+    "BusReportedDescription",
+    "Class",
+    "ClassGuid",
+    "Compid",
+    "ContainerId",
+    "Description",
+    "DeviceState",
+    "DriverId",
+    "DriverName",
+    "DriverPackageStrongName",
+    "DriverVerDate",
+    "DriverVerVersion",
+    "Enumerator",
+    "HWID",
+    "Inf",
+    "InstallState",
+    "LowerClassFilters",
+    "LowerFilters",
+    "Manufacturer",
+    "MatchingId",
+    "Model",
+    "ParentId",
+    "ProblemCode",
+    "Provider",
+    "Service",
+    "Stackid",
+    "UpperClassFilters",
+    "UpperFilters",
+];
+
+const DRIVER_BINARY_COLUMNS: [&str; 20] = [
+    "KeyName",
+    "KeyLastWriteTimestamp",
+    "DriverTimeStamp",
+    "DriverLastWriteTime",
+    "DriverName",
+    "DriverInBox",
+    "DriverIsKernelMode",
+    "DriverSigned",
+    "DriverCheckSum",
+    "DriverCompany",
+    "DriverId",
+    "DriverPackageStrongName",
+    "DriverType",
+    "DriverVersion",
+    "ImageSize",
+    "Inf",
+    "Product",
+    "ProductVersion",
+    "Service",
+    "WdfVersion",
+];
+
+const DRIVER_PACKAGE_COLUMNS: [&str; 12] = [
+    "KeyName",
+    "KeyLastWriteTimestamp",
+    "Date",
+    "Class",
+    "Directory",
+    "DriverInBox",
+    "Hwids",
+    "Inf",
+    "Provider",
+    "SubmissionId",
+    "SYSFILE",
+    "Version",
+];
+
+/// Writes one CSV file per inventory category into a folder, using the file names of AmcacheParser.
+///
+/// ```rust,ignore
+/// CsvExporter::new("./out").prefix("20191027100700_").write_all(&am_cache)?;
+/// ```
+pub struct CsvExporter {
+    folder: PathBuf,
+    prefix: String,
+}
+
+impl CsvExporter {
+    pub fn new<P: AsRef<Path>>(folder: P) -> Self {
+        Self {
+            folder: folder.as_ref().to_path_buf(),
+            prefix: String::new(),
+        }
+    }
+
+    /// Prefix added to each file name. AmcacheParser uses the execution timestamp.
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    fn create(&self, name: &str) -> ForensicResult<File> {
+        Ok(File::create(
+            self.folder.join(format!("{}{}", self.prefix, name)),
+        )?)
+    }
+
+    /// Writes every supported category
+    pub fn write_all<R: RegistryReader>(&self, am_cache: &AmCache<R>) -> ForensicResult<()> {
+        self.write_file_entries(am_cache)?;
+        self.write_program_entries(am_cache)?;
+        self.write_shortcuts(am_cache)?;
+        self.write_device_containers(am_cache)?;
+        self.write_device_pnps(am_cache)?;
+        self.write_driver_binaries(am_cache)?;
+        self.write_driver_packages(am_cache)
+    }
+
+    /// Writes the file entries split in associated (their ProgramId belongs to an InventoryApplication) and unassociated.
+    /// Without InventoryApplication every file is unassociated.
+    /// The Description column is always blank: InventoryApplicationFile has no description value.
+    pub fn write_file_entries<R: RegistryReader>(
+        &self,
+        am_cache: &AmCache<R>,
+    ) -> ForensicResult<()> {
+        let programs: BTreeMap<String, String> = match am_cache.applications() {
+            Ok(iter) => iter.map(|v| (v.program_id, v.name)).collect(),
+            Err(ForensicError::Missing(_)) => BTreeMap::new(),
+            Err(e) => return Err(e),
+        };
+        let (associated, unassociated): (Vec<_>, Vec<_>) = am_cache
+            .application_files()?
+            .partition(|v| programs.contains_key(&v.program_id));
+        write_file_entries(
+            self.create(ASSOCIATED_FILE_ENTRIES)?,
+            associated.iter().map(|v| {
+                let name = programs.get(&v.program_id).map(|v| v.as_str());
+                (name.unwrap_or_default(), v)
+            }),
+        )?;
+        write_file_entries(
+            self.create(UNASSOCIATED_FILE_ENTRIES)?,
+            unassociated.iter().map(|v| ("Unassociated", v)),
+        )
+    }
+
+    pub fn write_program_entries<R: RegistryReader>(
+        &self,
+        am_cache: &AmCache<R>,
+    ) -> ForensicResult<()> {
+        write_program_entries(self.create(PROGRAM_ENTRIES)?, am_cache.applications()?)
+    }
+
+    pub fn write_shortcuts<R: RegistryReader>(&self, am_cache: &AmCache<R>) -> ForensicResult<()> {
+        write_shortcuts(self.create(SHORTCUTS)?, am_cache.application_shortcuts()?)
+    }
+
+    pub fn write_device_containers<R: RegistryReader>(
+        &self,
+        am_cache: &AmCache<R>,
+    ) -> ForensicResult<()> {
+        write_device_containers(
+            self.create(DEVICE_CONTAINERS)?,
+            am_cache.device_containers()?,
+        )
+    }
+
+    pub fn write_device_pnps<R: RegistryReader>(
+        &self,
+        am_cache: &AmCache<R>,
+    ) -> ForensicResult<()> {
+        write_device_pnps(self.create(DEVICE_PNPS)?, am_cache.device_pnps()?)
+    }
+
+    pub fn write_driver_binaries<R: RegistryReader>(
+        &self,
+        am_cache: &AmCache<R>,
+    ) -> ForensicResult<()> {
+        write_driver_binaries(self.create(DRIVER_BINARIES)?, am_cache.driver_binaries()?)
+    }
+
+    pub fn write_driver_packages<R: RegistryReader>(
+        &self,
+        am_cache: &AmCache<R>,
+    ) -> ForensicResult<()> {
+        write_driver_packages(self.create(DRIVER_PACKAGES)?, am_cache.driver_package()?)
+    }
+}
+
+/// Writes file entries with the columns of Amcache_AssociatedFileEntries.csv / Amcache_UnassociatedFileEntries.csv. Each file comes with the name of its application.
+///
+/// The Description column is always blank: InventoryApplicationFile has no description value.
+pub fn write_file_entries<'a, W, I>(writer: W, files: I) -> ForensicResult<()>
+where
+    W: Write,
+    I: Iterator<Item = (&'a str, &'a InventoryApplicationFile)>,
+{
+    let mut csv = writer_with_header(writer, &FILE_ENTRY_COLUMNS)?;
+    for (app_name, file) in files {
+        let name = if file.name.is_empty() {
+            file_name(&file.path)
+        } else {
+            file.name.as_str()
+        };
+        let extension = match name.rsplit_once('.') {
+            Some((_, ext)) => format!(".{}", ext),
+            None => String::new(),
+        };
+        write_record(
+            &mut csv,
+            [
+                app_name,
+                &file.program_id,
+                &ez_time(&file.timestamp),
+                &hash_or_raw(file.sha1(), &file.hash),
+                bool_str(file.is_os_component),
+                &file.path,
+                name,
+                &extension,
+                &date_or_raw(&file.link_date, file.link_date_filetime),
+                &file.product_name,
+                &file.size.to_string(),
+                &file.version,
+                &file.product_version,
+                &file.long_path_hash,
+                &file.bin_type,
+                bool_str(file.is_pe_file),
+                &file.bin_file_version,
+                &file.bin_product_version,
+                &file.usn.to_string(),
+                &file.language.to_string(),
+                "",
+            ],
+        )?;
+    }
+    flush(csv)
+}
+
+/// Writes programs with the columns of Amcache_ProgramEntries.csv
+pub fn write_program_entries<W, I>(writer: W, apps: I) -> ForensicResult<()>
+where
+    W: Write,
+    I: Iterator<Item = InventoryApplication>,
+{
+    let mut csv = writer_with_header(writer, &PROGRAM_ENTRY_COLUMNS)?;
+    for app in apps {
+        write_record(
+            &mut csv,
+            [
+                app.program_id.as_str(),
+                &ez_time(&app.timestamp),
+                &app.name,
+                &app.version,
+                &app.publisher,
+                &date_or_raw(&app.install_date, app.install_date_filetime),
+                &app.os_version_at_install_time,
+                &app.bundle_manifest_path,
+                bool_str(app.hidden_arp),
+                bool_str(app.inbox_modern_app),
+                &app.language.to_string(),
+                &app.manifest_path,
+                &app.msi_package_code,
+                &app.msi_product_code,
+                &app.package_full_name,
+                &app.program_instance_id,
+                &app.registry_key_path,
+                &app.root_dir_path,
+                &app.source,
+                &app.store_app_type,
+                &app.r#type,
+                &app.uninstall_string,
+            ],
+        )?;
+    }
+    flush(csv)
+}
+
+/// Writes shortcuts with the columns of Amcache_ShortCuts.csv
+pub fn write_shortcuts<W, I>(writer: W, shortcuts: I) -> ForensicResult<()>
+where
+    W: Write,
+    I: Iterator<Item = InventoryApplicationShortcut>,
+{
+    let mut csv = writer_with_header(writer, &SHORTCUT_COLUMNS)?;
+    for shortcut in shortcuts {
+        write_record(
+            &mut csv,
+            [
+                shortcut.key_name.as_str(),
+                shortcut.path.as_str(),
+                &ez_time(&shortcut.timestamp),
+            ],
+        )?;
+    }
+    flush(csv)
+}
+
+/// Writes device containers with the columns of Amcache_DeviceContainers.csv
+pub fn write_device_containers<W, I>(writer: W, devices: I) -> ForensicResult<()>
+where
+    W: Write,
+    I: Iterator<Item = InventoryDeviceContainer>,
+{
+    let mut csv = writer_with_header(writer, &DEVICE_CONTAINER_COLUMNS)?;
+    for device in devices {
+        write_record(
+            &mut csv,
+            [
+                device.key_name.as_str(),
+                &ez_time(&device.timestamp),
+                &device.categories,
+                &device.discovery_method.to_string(),
+                &device.friendly_name,
+                &device.icon,
+                bool_str(device.is_active),
+                bool_str(device.is_connected),
+                bool_str(device.is_machine_container),
+                bool_str(device.is_networked),
+                bool_str(device.is_paired),
+                &device.manufacturer,
+                &device.model_id,
+                &device.model_name,
+                &device.model_number,
+                &device.primary_category,
+                &device.state.to_string(),
+            ],
+        )?;
+    }
+    flush(csv)
+}
+
+/// Writes Plug and Play devices with the columns of Amcache_DevicePnps.csv
+pub fn write_device_pnps<W, I>(writer: W, devices: I) -> ForensicResult<()>
+where
+    W: Write,
+    I: Iterator<Item = InventoryDevicePnp>,
+{
+    let mut csv = writer_with_header(writer, &DEVICE_PNP_COLUMNS)?;
+    for device in devices {
+        write_record(
+            &mut csv,
+            [
+                device.key_name.as_str(),
+                &ez_time(&device.timestamp),
+                &device.bus_reported_description,
+                &device.class,
+                &device.class_guid,
+                &device.compid,
+                &device.container_id,
+                &device.description,
+                &device.device_state.to_string(),
+                &hash_or_raw(device.sha1(), &device.driver_id),
+                &device.driver_name,
+                &device.driver_package_strong_name,
+                &date_or_raw(&device.driver_ver_date, device.driver_ver_date_filetime),
+                &device.driver_ver_version,
+                &device.enumerator,
+                &device.hwid,
+                &device.inf,
+                &device.install_state.to_string(),
+                &device.lower_class_filters,
+                &device.lower_filters,
+                &device.manufacturer,
+                &device.matching_id,
+                &device.model,
+                &device.parent_id,
+                &device.problem_code.to_string(),
+                &device.provider,
+                &device.service,
+                &device.stackid,
+                &device.upper_class_filters,
+                &device.upper_filters,
+            ],
+        )?;
+    }
+    flush(csv)
+}
+
+/// Writes driver binaries with the columns of Amcache_DriveBinaries.csv
+pub fn write_driver_binaries<W, I>(writer: W, drivers: I) -> ForensicResult<()>
+where
+    W: Write,
+    I: Iterator<Item = InventoryDriverBinary>,
+{
+    let mut csv = writer_with_header(writer, &DRIVER_BINARY_COLUMNS)?;
+    for driver in drivers {
+        write_record(
+            &mut csv,
+            [
+                driver.key_name.as_str(),
+                &ez_time(&driver.timestamp),
+                &unix_time(driver.driver_timestamp),
+                &date_or_raw(
+                    &driver.driver_last_write_time,
+                    driver.driver_last_write_filetime,
+                ),
+                &driver.driver_name,
+                bool_str(driver.driver_in_box),
+                bool_str(driver.driver_is_kernel_mode),
+                bool_str(driver.driver_signed),
+                &driver.driver_check_sum.to_string(),
+                &driver.driver_company,
+                &hash_or_raw(driver.sha1(), &driver.driver_id),
+                &driver.driver_package_strong_name,
+                &driver.driver_type.to_string(),
+                &driver.driver_version,
+                &driver.image_size.to_string(),
+                &driver.inf,
+                &driver.product,
+                &driver.product_version,
+                &driver.service,
+                &driver.wdf_version,
+            ],
+        )?;
+    }
+    flush(csv)
+}
+
+/// Writes driver packages with the columns of Amcache_DriverPackages.csv
+pub fn write_driver_packages<W, I>(writer: W, packages: I) -> ForensicResult<()>
+where
+    W: Write,
+    I: Iterator<Item = InventoryDriverPackage>,
+{
+    let mut csv = writer_with_header(writer, &DRIVER_PACKAGE_COLUMNS)?;
+    for package in packages {
+        write_record(
+            &mut csv,
+            [
+                package.key_name.as_str(),
+                &ez_time(&package.timestamp),
+                &date_or_raw(&package.date, package.date_filetime),
+                &package.class,
+                &package.directory,
+                bool_str(package.driver_inbox),
+                &package.hwids,
+                &package.inf,
+                &package.provider,
+                &package.submission_id,
+                &package.sysfile,
+                &package.version,
+            ],
+        )?;
+    }
+    flush(csv)
+}
+
+fn csv_error(e: csv::Error) -> ForensicError {
+    ForensicError::Other(e.to_string())
+}
+
+fn writer_with_header<W: Write>(writer: W, columns: &[&str]) -> ForensicResult<csv::Writer<W>> {
+    let mut csv = csv::Writer::from_writer(writer);
+    csv.write_record(columns).map_err(csv_error)?;
+    Ok(csv)
+}
+
+fn write_record<W: Write, const N: usize>(
+    csv: &mut csv::Writer<W>,
+    record: [&str; N],
+) -> ForensicResult<()> {
+    csv.write_record(record).map_err(csv_error)
+}
+
+fn flush<W: Write>(mut csv: csv::Writer<W>) -> ForensicResult<()> {
+    Ok(csv.flush()?)
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit(['\\', '/']).next().unwrap_or(path)
+}
+
+/// Timestamp format used by AmcacheParser: `yyyy-MM-dd HH:mm:ss`
+fn ez_time(time: &Filetime) -> String {
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        time.year(),
+        time.month(),
+        time.day(),
+        time.hour(),
+        time.minute(),
+        time.second()
+    )
+}
+
+fn date_or_raw(raw: &str, time: Option<Filetime>) -> String {
+    match time.or_else(|| parse_date(raw)) {
+        Some(time) => ez_time(&time),
+        None => raw.to_string(),
+    }
+}
+
+/// SHA-1 without the `0000` prefix, or the raw FileId/DriverId when it is not a valid hash
+fn hash_or_raw(hash: Option<Sha1Hash>, raw: &str) -> String {
+    match hash {
+        Some(hash) => hash.to_string(),
+        None => raw.to_string(),
+    }
+}
+
+fn unix_time(timestamp: u32) -> String {
+    from_unix_seconds(timestamp as u64)
+        .map(|v| ez_time(&v))
+        .unwrap_or_default()
+}
+
+fn bool_str(value: u32) -> &'static str {
+    if value == 0 {
+        "False"
+    } else {
+        "True"
+    }
+}
@@ -1,2 +1,4 @@
 pub mod amcache;
-pub mod common;
\ No newline at end of file
+pub mod common;
+#[cfg(feature = "csv")]
+pub mod export;
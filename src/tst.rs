@@ -1,9 +1,9 @@
-use std::path::Path;
+use forensic_rs::{core::fs::{ChRootFileSystem, StdVirtualFS}, err::{ForensicError, ForensicResult}, traits::{registry::{RegHiveKey, RegValue, RegistryKeyInfo, RegistryReader}, vfs::{VirtualFile, VirtualFileSystem}}, utils::testing::TestingRegistry};
+use frnsc_hive::reader::HiveRegistryReader;
 
-use forensic_rs::{core::fs::{ChRootFileSystem, StdVirtualFS}, err::ForensicResult, traits::vfs::VirtualFileSystem};
-use frnsc_hive::reader::{open_hive_with_logs, HiveRegistryReader};
+use crate::common::{format::{supported_subkeys, AmCacheGeneration, OsVersionSource}, schema::{FieldDescriptor, FieldType, Schema}, values::{AnomalyKind, FieldAnomaly, ParseMode}, RecordIter};
 
-use super::AmCache;
+use super::{AmCache, RegistryReaderRef, DEFAULT_AMCACHE_PATH};
 
 fn obtain_am_cache() -> AmCache<HiveRegistryReader> {
     let fs = StdVirtualFS::new().duplicate();
@@ -12,12 +12,66 @@ fn obtain_am_cache() -> AmCache<HiveRegistryReader> {
 }
 
 fn load_am_cache_from_fs(fs : &mut Box<dyn VirtualFileSystem>) -> ForensicResult<AmCache<HiveRegistryReader>>{
-    let mut reader = HiveRegistryReader::new();
-    let hive_file = open_hive_with_logs(fs, Path::new(r"C:\Windows\AppCompat\Programs"), "Amcache.hve").unwrap();
-    reader.add_other("Amcache", hive_file);
-    Ok(AmCache {
-        reader
-    })
+    AmCache::from_fs(fs, DEFAULT_AMCACHE_PATH)
+}
+
+/// In-memory hive for keys missing in the test artifact. Wraps TestingRegistry, whose key_at panics past the last subkey.
+/// The subkeys named in the second field cannot be opened.
+struct TestHive(TestingRegistry, Vec<String>);
+
+impl TestHive {
+    fn new() -> Self {
+        Self(TestingRegistry::empty(), Vec::new())
+    }
+
+    /// Makes open_key fail for the subkeys with this name
+    fn deny_key(&mut self, name : &str) {
+        self.1.push(name.to_string());
+    }
+
+    fn add_value(&mut self, path : &str, value : &str, data : RegValue) {
+        self.0.add_value(&format!(r"HKLM\Amcache\{}", path), value, data);
+    }
+
+    fn am_cache(self) -> AmCache<TestHive> {
+        AmCache::builder(self).root_key(RegHiveKey::HkeyLocalMachine).root_path("Amcache").build()
+    }
+}
+
+impl RegistryReader for TestHive {
+    fn from_file(&self, file: Box<dyn VirtualFile>) -> ForensicResult<Box<dyn RegistryReader>> {
+        self.0.from_file(file)
+    }
+    fn from_fs(&self, fs: Box<dyn VirtualFileSystem>) -> ForensicResult<Box<dyn RegistryReader>> {
+        self.0.from_fs(fs)
+    }
+    fn open_key(&self, hkey: RegHiveKey, key_name: &str) -> ForensicResult<RegHiveKey> {
+        if self.1.iter().any(|v| v == key_name) {
+            return Err(ForensicError::PermissionError);
+        }
+        self.0.open_key(hkey, key_name)
+    }
+    fn read_value(&self, hkey: RegHiveKey, value_name: &str) -> ForensicResult<RegValue> {
+        self.0.read_value(hkey, value_name)
+    }
+    fn enumerate_values(&self, hkey: RegHiveKey) -> ForensicResult<Vec<String>> {
+        self.0.enumerate_values(hkey)
+    }
+    fn enumerate_keys(&self, hkey: RegHiveKey) -> ForensicResult<Vec<String>> {
+        self.0.enumerate_keys(hkey)
+    }
+    fn key_at(&self, hkey: RegHiveKey, pos: u32) -> ForensicResult<String> {
+        self.0.enumerate_keys(hkey)?.get(pos as usize).cloned().ok_or(ForensicError::NoMoreData)
+    }
+    fn value_at(&self, hkey: RegHiveKey, pos: u32) -> ForensicResult<String> {
+        self.0.enumerate_values(hkey)?.get(pos as usize).cloned().ok_or(ForensicError::NoMoreData)
+    }
+    fn key_info(&self, hkey: RegHiveKey) -> ForensicResult<RegistryKeyInfo> {
+        self.0.key_info(hkey)
+    }
+    fn close_key(&self, hkey: RegHiveKey) {
+        self.0.close_key(hkey)
+    }
 }
 
 #[test]
@@ -25,6 +79,614 @@ fn should_read_amcache() {
     let _am_cache = obtain_am_cache();
 }
 
+#[test]
+fn should_use_custom_mount_name() {
+    use frnsc_hive::reader::open_hive_with_logs;
+    use std::path::Path;
+    let fs = StdVirtualFS::new().duplicate();
+    let mut fs = ChRootFileSystem::new("./artifacts", fs).duplicate();
+    let mut reader = HiveRegistryReader::new();
+    let hive_file = open_hive_with_logs(&mut fs, Path::new(r"C:\Windows\AppCompat\Programs"), "Amcache.hve").unwrap();
+    reader.add_other(r"HOST01\Amcache", hive_file);
+    let am_cache = AmCache::builder(reader).mount_name(r"HOST01\Amcache").build();
+    assert_eq!(5, am_cache.driver_package().unwrap().count());
+}
+
+#[test]
+fn should_share_reader_between_hives() {
+    use forensic_rs::traits::registry::{RegHiveKey, RegistryReader};
+    use frnsc_hive::reader::open_hive_with_logs;
+    use std::path::Path;
+    let fs = StdVirtualFS::new().duplicate();
+    let mut fs = ChRootFileSystem::new("./artifacts", fs).duplicate();
+    let mut reader = HiveRegistryReader::new();
+    for host in ["HOST01", "HOST02"] {
+        let hive_file = open_hive_with_logs(&mut fs, Path::new(r"C:\Windows\AppCompat\Programs"), "Amcache.hve").unwrap();
+        reader.add_other(&format!(r"{}\Amcache", host), hive_file);
+    }
+    let host1 = AmCache::builder(RegistryReaderRef(&reader)).mount_name(r"HOST01\Amcache").build();
+    let host2 = AmCache::builder(RegistryReaderRef(&reader)).mount_name(r"HOST02\Amcache").build();
+    assert_eq!(5, host1.driver_package().unwrap().count());
+    assert_eq!(5, host2.driver_package().unwrap().count());
+
+    let root = reader.open_key(RegHiveKey::Hkey(0), r"HOST02\Amcache").unwrap();
+    let from_key = AmCache::builder(RegistryReaderRef(&reader)).root_key(root).root_path("").build();
+    assert_eq!(5, from_key.driver_package().unwrap().count());
+}
+
+#[test]
+fn should_report_errors_per_record() {
+    let am_cache = obtain_am_cache();
+    let results : Vec<_> = am_cache.application_files().unwrap().results().collect();
+    assert_eq!(46, results.len());
+    assert!(results.iter().all(|v| v.is_ok()));
+    assert_eq!(46, am_cache.application_files().unwrap().count());
+
+    let mut hive = TestHive::new();
+    for (subkey, path) in [("0000a1", "c:\\a.exe"), ("0000a2", "c:\\b.exe"), ("0000a3", "c:\\c.exe")] {
+        hive.add_value(&format!(r"Root\InventoryApplicationFile\{}", subkey), "LowerCaseLongPath", RegValue::SZ(path.into()));
+    }
+    hive.deny_key("0000a2");
+    let am_cache = hive.am_cache();
+    let results : Vec<_> = am_cache.application_files().unwrap().results().collect();
+    assert_eq!(3, results.len());
+    assert!(matches!(results[1], Err(ForensicError::PermissionError)));
+    let files : Vec<_> = am_cache.application_files().unwrap().collect();
+    assert_eq!(vec!["0000a1", "0000a3"], files.iter().map(|v| v.key_name.as_str()).collect::<Vec<_>>());
+    assert_eq!("c:\\c.exe", files[1].path);
+}
+
+#[test]
+fn should_report_field_anomalies() {
+    let am_cache = obtain_am_cache();
+    let devices : Vec<_> = am_cache.device_containers().unwrap().collect();
+    let computer = devices.iter().find(|v| v.manufacturer == "QEMU").unwrap();
+    assert_eq!(1, computer.is_machine_container);
+    assert!(computer.anomalies.iter().any(|v| v.value == "DiscoveryMethod" && v.kind == AnomalyKind::Missing));
+    let drivers : Vec<_> = am_cache.driver_binaries().unwrap().collect();
+    assert!(drivers.iter().all(|v| v.driver_in_box == 1 || v.driver_in_box == 0));
+}
+
+#[test]
+fn should_parse_in_strict_mode() {
+    let am_cache = obtain_am_cache();
+    let reader = am_cache.reader;
+    let am_cache = AmCache::builder(reader).parse_mode(ParseMode::Strict).build();
+    assert!(am_cache.driver_binaries().unwrap().results().all(|v| v.is_ok()));
+    assert_eq!(6, am_cache.device_containers().unwrap().count());
+}
+
+#[test]
+fn should_report_invalid_values() {
+    let build = |mode : ParseMode| {
+        let mut hive = TestHive::new();
+        let app = r"Root\InventoryApplication";
+        hive.add_value(&format!(r"{}\0000a1", app), "Name", RegValue::SZ("Clean".into()));
+        hive.add_value(&format!(r"{}\0000a1", app), "Language", RegValue::SZ("1033".into()));
+        hive.add_value(&format!(r"{}\0000a2", app), "Language", RegValue::SZ("english".into()));
+        hive.add_value(&format!(r"{}\0000a3", app), "HiddenArp", RegValue::QWord(1 << 40));
+        hive.add_value(&format!(r"{}\0000a4", app), "Name", RegValue::DWord(7));
+        AmCache::builder(hive).root_key(RegHiveKey::HkeyLocalMachine).root_path("Amcache").parse_mode(mode).build()
+    };
+    let am_cache = build(ParseMode::Lenient);
+    let apps : Vec<_> = am_cache.applications().unwrap().collect();
+    assert_eq!(4, apps.len());
+    let kind = |key_name : &str, value : &str| {
+        let app = apps.iter().find(|v| v.key_name == key_name).unwrap();
+        app.anomalies.iter().find(|v| v.value == value).map(|v| v.kind)
+    };
+    assert_eq!(1033, apps[0].language);
+    assert_eq!(None, kind("0000a1", "Language"));
+    assert_eq!(Some(AnomalyKind::Corrupt), kind("0000a2", "Language"));
+    assert_eq!(0, apps[1].language);
+    assert_eq!(Some(AnomalyKind::Corrupt), kind("0000a3", "HiddenArp"));
+    assert_eq!(0, apps[2].hidden_arp);
+    assert_eq!(Some(AnomalyKind::WrongType), kind("0000a4", "Name"));
+    assert_eq!("", apps[3].name);
+
+    let am_cache = build(ParseMode::Strict);
+    let results : Vec<_> = am_cache.applications().unwrap().results().collect();
+    assert_eq!(4, results.len());
+    assert_eq!("Clean", results[0].as_ref().unwrap().name);
+    assert!(results[1..].iter().all(|v| matches!(v, Err(ForensicError::BadFormat(_)))));
+}
+
+#[test]
+fn should_truncate_strings_at_nul() {
+    let am_cache = obtain_am_cache();
+    let packages : Vec<_> = am_cache.driver_package().unwrap().collect();
+    assert!(packages.iter().any(|v| v.sysfile == "vioser.sys"));
+    assert!(packages.iter().all(|v| v.slack.is_empty()));
+    let drivers : Vec<_> = am_cache.driver_binaries().unwrap().collect();
+    assert!(drivers.iter().all(|v| !v.driver_name.contains('\0')));
+
+    let am_cache = AmCache::builder(am_cache.reader).keep_slack(true).build();
+    let vioser = am_cache.driver_package().unwrap().find(|v| v.sysfile == "vioser.sys").unwrap();
+    assert_eq!(&vec![0, 0, 1, 0], vioser.slack.get("SYSFILE").unwrap());
+
+    // The reader decodes the strings before the parser sees them: an unpaired surrogate in the slack comes back as U+FFFD
+    let mut hive = TestHive::new();
+    let path = String::from_utf16_lossy(&[0x61, 0x62, 0, 0xD800, 0x63]);
+    hive.add_value(r"Root\InventoryApplicationFile\0000a1", "LowerCaseLongPath", RegValue::SZ(path));
+    let am_cache = AmCache::builder(hive).root_key(RegHiveKey::HkeyLocalMachine).root_path("Amcache").keep_slack(true).build();
+    let file = am_cache.application_files().unwrap().next().unwrap();
+    assert_eq!("ab", file.path);
+    assert_eq!(&vec![0xFD, 0xFF, 0x63, 0], file.slack.get("LowerCaseLongPath").unwrap());
+}
+
+#[test]
+fn should_parse_dates() {
+    let am_cache = obtain_am_cache();
+    let chrome = am_cache.application_files().unwrap().find(|v| v.path.ends_with(r"application\chrome.exe")).unwrap();
+    assert_eq!("08/23/2019 05:00:00", chrome.link_date);
+    assert_eq!("23-08-2019 05:00:00", format!("{:?}", chrome.link_date_filetime.unwrap()));
+    let ohci = am_cache.driver_binaries().unwrap().find(|v| v.driver_name == "1394ohci.sys").unwrap();
+    assert_eq!("16-07-2016 13:18:02", format!("{:?}", ohci.driver_last_write_filetime.unwrap()));
+    let balloon = am_cache.driver_package().unwrap().find(|v| v.sysfile == "balloon.sys").unwrap();
+    assert_eq!("12-04-2019 00:00:00", format!("{:?}", balloon.date_filetime.unwrap()));
+    assert!(balloon.date_filetime.unwrap().filetime() < balloon.timestamp.filetime());
+    for app in am_cache.applications().unwrap() {
+        assert_eq!(app.install_date.is_empty(), app.install_date_filetime.is_none());
+    }
+    use crate::common::time::{from_iso8601, parse_date};
+    assert!(parse_date("02/31/2020 00:00:00").is_none());
+    assert!(parse_date("04/31/2019").is_none());
+    assert!(parse_date("02/29/2019").is_none());
+    assert!(parse_date("1900-2-29").is_none());
+    assert_eq!("29-02-2020 10:00:00", format!("{:?}", parse_date("02/29/2020 10:00:00").unwrap()));
+    assert_eq!("29-02-2000 00:00:00", format!("{:?}", parse_date("2000-2-29").unwrap()));
+    assert_eq!("30-04-2019 00:00:00", format!("{:?}", parse_date("04-30-2019").unwrap()));
+    assert!(from_iso8601("2019-04-31T00:00:00Z").is_none());
+    assert!(from_iso8601("2020-02-29T00:00:00Z").is_some());
+}
+
+#[test]
+fn should_read_full_file_schema() {
+    let am_cache = obtain_am_cache();
+    let chrome = am_cache.application_files().unwrap().find(|v| v.path.ends_with(r"application\chrome.exe")).unwrap();
+    assert_eq!("chrome.exe", chrome.name);
+    assert_eq!("76.0.3809.132", chrome.bin_file_version);
+    assert_eq!("76.0.3809.132", chrome.bin_product_version);
+    assert_eq!("chrome.exe|467d7dab451d03b5", chrome.long_path_hash);
+    assert_eq!(1, chrome.is_pe_file);
+    assert_eq!(0, chrome.is_os_component);
+    // Not written by Windows 10 1607
+    assert!(chrome.anomalies.iter().any(|v| v.value == "OriginalFileName" && v.kind == AnomalyKind::Missing));
+
+    let mut hive = TestHive::new();
+    let file = r"Root\InventoryApplicationFile\svc.exe|5c4a8e2b9d1f3a70";
+    hive.add_value(file, "LowerCaseLongPath", RegValue::SZ(r"c:\windows\temp\svc.exe".into()));
+    hive.add_value(file, "Name", RegValue::SZ("svc.exe".into()));
+    hive.add_value(file, "OriginalFileName", RegValue::SZ("mimikatz.exe".into()));
+    hive.add_value(file, "BinProductVersion", RegValue::SZ("2.2.0.0".into()));
+    hive.add_value(file, "AppxPackageFullName", RegValue::SZ("".into()));
+    let file = hive.am_cache().application_files().unwrap().next().unwrap();
+    assert_ne!(file.name, file.original_file_name);
+    assert_eq!("mimikatz.exe", file.original_file_name);
+    assert_eq!("2.2.0.0", file.bin_product_version);
+    // ProductVersion does not exist, its alias BinProductVersion is used
+    assert_eq!("2.2.0.0", file.product_version);
+    assert!(!file.anomalies.iter().any(|v| v.value == "ProductVersion"));
+}
+
+#[test]
+fn should_decode_sha1() {
+    let am_cache = obtain_am_cache();
+    let chrome = am_cache.application_files().unwrap().find(|v| v.path.ends_with(r"application\chrome.exe")).unwrap();
+    assert_eq!("f6af6cd298f660ff5bb4f89398d1d3edac020a7d", chrome.sha1().unwrap().to_string());
+    for driver in am_cache.driver_binaries().unwrap() {
+        assert_eq!(driver.driver_id.is_empty(), driver.sha1().is_none());
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn should_serialize_records() {
+    use crate::common::{app::InventoryApplication, app_file::InventoryApplicationFile, census::DeviceCensus};
+    let am_cache = obtain_am_cache();
+    let chrome = am_cache.application_files().unwrap().find(|v| v.path.ends_with(r"application\chrome.exe")).unwrap();
+    let json = serde_json::to_value(&chrome).unwrap();
+    assert_eq!("2019-09-04T21:19:11.6528038Z", json["timestamp"]);
+    assert_eq!("2019-08-23T05:00:00.0000000Z", json["link_date_filetime"]);
+    assert_eq!("google chrome", json["product_name"]);
+    let parsed : InventoryApplicationFile = serde_json::from_value(json).unwrap();
+    assert_eq!(chrome.timestamp.filetime(), parsed.timestamp.filetime());
+    assert_eq!(chrome.path, parsed.path);
+
+    let app = am_cache.applications().unwrap().next().unwrap();
+    let json = serde_json::to_value(&app).unwrap();
+    assert_eq!("Application", json["type"]);
+    assert!(json["install_date_filetime"].is_null());
+    let _ : InventoryApplication = serde_json::from_value(json).unwrap();
+
+    // Exports without the anomalies, slack and extra maps
+    let mut json = serde_json::to_value(&chrome).unwrap();
+    for name in ["anomalies", "slack", "extra"] {
+        json.as_object_mut().unwrap().remove(name);
+    }
+    let parsed : InventoryApplicationFile = serde_json::from_value(json).unwrap();
+    assert!(parsed.anomalies.is_empty() && parsed.slack.is_empty());
+    let mut json = serde_json::to_value(am_cache.device_census().unwrap()).unwrap();
+    for name in ["anomalies", "slack", "extra"] {
+        json.as_object_mut().unwrap().remove(name);
+    }
+    let parsed : DeviceCensus = serde_json::from_value(json).unwrap();
+    assert_eq!("ChitonSRV", parsed.device_name);
+
+    assert_eq!("windows11", serde_json::to_value(AmCacheGeneration::Windows11).unwrap());
+    assert_eq!("in_box_driver", serde_json::to_value(OsVersionSource::InBoxDriver).unwrap());
+}
+
+#[cfg(feature = "csv")]
+#[test]
+fn should_export_amcacheparser_csv() {
+    use crate::export::{write_device_containers, write_driver_binaries, write_shortcuts};
+    let am_cache = obtain_am_cache();
+    let mut out = Vec::new();
+    write_shortcuts(&mut out, am_cache.application_shortcuts().unwrap()).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    let mut lines = out.lines();
+    assert_eq!(Some("KeyName,LnkName,KeyLastWriteTimestamp"), lines.next());
+    assert_eq!(am_cache.application_shortcuts().unwrap().count(), lines.count());
+
+    let mut out = Vec::new();
+    write_driver_binaries(&mut out, am_cache.driver_binaries().unwrap()).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    let driver = out.lines().find(|v| v.contains(",1394ohci.sys,")).unwrap();
+    assert_eq!("c:/windows/system32/drivers/1394ohci.sys,2019-10-27 10:07:06,2016-07-16 02:21:36,2016-07-16 13:18:02,1394ohci.sys,True,True,True,285843,Microsoft Corporation,895407cb018368e62fc360b972a8b0da7e729662,,8650778,10.0.14393.0,262144,,Microsoft® Windows® Operating System,10.0.14393.0,1394ohci,", driver);
+
+    let mut out = Vec::new();
+    write_device_containers(&mut out, am_cache.device_containers().unwrap()).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    let computer = out.lines().find(|v| v.starts_with("{27db0821-3bf9-f71a-f96f-a53403857690},")).unwrap();
+    assert!(computer.contains(r#",CHITONSRV,"C:\Windows\System32\DDORes.dll,-2061",True,"#), "{}", computer);
+
+    // DriverId that is not a 0000 prefixed SHA-1 is exported as is
+    let mut hive = TestHive::new();
+    hive.add_value(r"Root\InventoryDriverBinary\acpi.sys", "DriverId", RegValue::SZ("0000acpi".into()));
+    let mut out = Vec::new();
+    write_driver_binaries(&mut out, hive.am_cache().driver_binaries().unwrap()).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert_eq!(Some("0000acpi"), out.lines().nth(1).unwrap().split(',').nth(10));
+
+    // Files are unassociated when the hive has no InventoryApplication
+    let mut hive = TestHive::new();
+    hive.add_value(r"Root\InventoryApplicationFile\calc.exe|1a2b3c4d", "LowerCaseLongPath", RegValue::SZ(r"c:\windows\system32\calc.exe".into()));
+    let folder = std::env::temp_dir().join(format!("frnsc-amcache-csv-{}", std::process::id()));
+    std::fs::create_dir_all(&folder).unwrap();
+    crate::export::CsvExporter::new(&folder).write_file_entries(&hive.am_cache()).unwrap();
+    let unassociated = std::fs::read_to_string(folder.join(crate::export::UNASSOCIATED_FILE_ENTRIES)).unwrap();
+    let associated = std::fs::read_to_string(folder.join(crate::export::ASSOCIATED_FILE_ENTRIES)).unwrap();
+    std::fs::remove_dir_all(&folder).unwrap();
+    assert!(unassociated.lines().nth(1).unwrap().starts_with("Unassociated,"));
+    assert_eq!(1, associated.lines().count());
+}
+
+#[test]
+fn should_read_pnp_devices() {
+    let am_cache = obtain_am_cache();
+    let devices : Vec<_> = am_cache.device_pnps().unwrap().collect();
+    assert_eq!(70, devices.len());
+    let usb_input = devices.iter().find(|v| v.enumerator == "usb" && v.class == "hidclass").unwrap();
+    assert_eq!("USB Input Device", usb_input.model);
+    assert_eq!("09-03-2019", usb_input.install_date);
+    assert_eq!("03-09-2019 00:00:00", format!("{:?}", usb_input.install_date_filetime.unwrap()));
+    let button = devices.iter().find(|v| v.hwid.starts_with(r"acpi\ven_acpi&dev_0010,")).unwrap();
+    assert_eq!(r"acpi_hal\pnp0c08\0", button.parent_id);
+    assert_eq!("hidinterrupt", button.service);
+    assert_eq!(31, button.problem_code);
+    assert_eq!("86436f8ee2ce1f354f5a355e52e706e2c7927cde", button.sha1().unwrap().to_string());
+}
+
+#[test]
+fn should_read_application_drivers() {
+    let mut hive = TestHive::new();
+    let driver = r"Root\InventoryApplicationDriver\00005e1d4d7f5c0b0c6bbe6f79ae30b2a8a45c0fd98f";
+    hive.add_value(driver, "DriverId", RegValue::SZ("00005e1d4d7f5c0b0c6bbe6f79ae30b2a8a45c0fd98f".into()));
+    hive.add_value(driver, "ProgramIds", RegValue::SZ("0000f519feec486de87ed73cb92d3cac802400000000,00000fe7579bd6b791cc0896934746a88b8c00000a0c".into()));
+    let no_id = r"Root\InventoryApplicationDriver\00009d9e4c0fdbd0d2db2ca2b0a2a1b8a8c7a2b0c4d5";
+    hive.add_value(no_id, "ProgramIds", RegValue::MultiSZ(vec!["0000f519feec486de87ed73cb92d3cac802400000000".into()]));
+    let am_cache = hive.am_cache();
+    let drivers : Vec<_> = am_cache.application_drivers().unwrap().collect();
+    assert_eq!(2, drivers.len());
+    assert_eq!("5e1d4d7f5c0b0c6bbe6f79ae30b2a8a45c0fd98f", drivers[0].sha1().unwrap().to_string());
+    assert_eq!(vec!["0000f519feec486de87ed73cb92d3cac802400000000", "00000fe7579bd6b791cc0896934746a88b8c00000a0c"], drivers[0].program_ids);
+    assert_eq!("00009d9e4c0fdbd0d2db2ca2b0a2a1b8a8c7a2b0c4d5", drivers[1].driver_id);
+    assert_eq!(1, drivers[1].program_ids.len());
+    assert!(obtain_am_cache().application_drivers().is_err());
+}
+
+#[test]
+fn should_read_application_frameworks() {
+    assert_eq!(0, obtain_am_cache().application_frameworks().unwrap().count());
+    let mut hive = TestHive::new();
+    let framework = r"Root\InventoryApplicationFramework\0000f65dd072877889a3ff2a18d76d9ad904264c0921";
+    hive.add_value(framework, "FileId", RegValue::SZ("0000f65dd072877889a3ff2a18d76d9ad904264c0921".into()));
+    hive.add_value(framework, "ProgramId", RegValue::SZ("0006551c26770e1f9e806ad7d0ed8d5254cc00000904".into()));
+    hive.add_value(framework, "Frameworks", RegValue::MultiSZ(vec!["Microsoft.NETCore.App 3.1.0".into(), "Microsoft.VC140.CRT".into()]));
+    let am_cache = hive.am_cache();
+    let framework = am_cache.application_frameworks().unwrap().next().unwrap();
+    assert_eq!("f65dd072877889a3ff2a18d76d9ad904264c0921", framework.sha1().unwrap().to_string());
+    assert_eq!("0006551c26770e1f9e806ad7d0ed8d5254cc00000904", framework.program_id);
+    assert_eq!(vec!["Microsoft.NETCore.App 3.1.0", "Microsoft.VC140.CRT"], framework.frameworks);
+    assert!(framework.anomalies.is_empty());
+}
+
+#[test]
+fn should_read_application_appvs() {
+    assert_eq!(0, obtain_am_cache().application_appvs().unwrap().count());
+    let mut hive = TestHive::new();
+    let package = r"Root\InventoryApplicationAppV\0000b7a1c2e7a0e4c0b8d6a4f5e2c1d3b4a5f6e7d8c9";
+    hive.add_value(package, "PackageGuid", RegValue::SZ("{4f1c9b2e-6a3d-4e1f-9c8b-7d2a5e6f1b3c}".into()));
+    hive.add_value(package, "VersionGuid", RegValue::SZ("{a2b3c4d5-e6f7-4a8b-9c0d-1e2f3a4b5c6d}".into()));
+    hive.add_value(package, "Name", RegValue::SZ("Notepad++".into()));
+    hive.add_value(package, "Publisher", RegValue::SZ("Notepad++ Team".into()));
+    let am_cache = hive.am_cache();
+    let package = am_cache.application_appvs().unwrap().next().unwrap();
+    assert_eq!("{4f1c9b2e-6a3d-4e1f-9c8b-7d2a5e6f1b3c}", package.package_guid);
+    assert_eq!("{a2b3c4d5-e6f7-4a8b-9c0d-1e2f3a4b5c6d}", package.version_guid);
+    assert_eq!("Notepad++", package.name);
+    assert_eq!("", package.version);
+    assert_eq!(1, package.anomalies.len());
+}
+
+#[test]
+fn should_read_device_census() {
+    let am_cache = obtain_am_cache();
+    let census = am_cache.device_census().unwrap();
+    assert_eq!("ServerStandardEval", census.os_edition);
+    assert_eq!(Some(14393), census.ie_build());
+    assert_eq!("ChitonSRV", census.device_name);
+    assert_eq!("SeaBIOS", census.firmware_manufacturer);
+    assert_eq!("01-04-2014 00:00:00", format!("{:?}", census.firmware_release_date_filetime.unwrap()));
+    assert_eq!("04-09-2019 20:17:12.459", format!("{:?}", census.os_oobe_filetime.unwrap()));
+    // REG_BINARY numbers
+    assert_eq!(2, census.processor_cores);
+    assert_eq!(2048, census.total_physical_ram);
+    assert_eq!(61440, census.primary_disk_total_capacity);
+    assert_eq!(0, census.is_domain_joined);
+    // "#" means unknown
+    assert_eq!("", census.hashed_domain);
+    assert_eq!(vec!["es-ES"], census.language_packs);
+    // Values without a field: root values and subkeys not mapped
+    assert_eq!(Some(&RegValue::DWord(1)), census.extra.get("WritePermissionsCheck"));
+    assert_eq!(Some(&RegValue::SZ("es-ES".into())), census.extra.get(r"Xbox\XboxConsolePreferredLanguage"));
+    assert!(census.extra.contains_key(r"Battery\InternalBatteryCapacityDesign"));
+    assert!(census.extra.contains_key(r"OS\RACw7Id"));
+    // Azure is listed by the hive but cannot be opened
+    assert_eq!(vec![FieldAnomaly { value : "Azure".into(), kind : AnomalyKind::Corrupt }], census.anomalies);
+    let strict = AmCache::builder(am_cache.reader).parse_mode(ParseMode::Strict).build();
+    assert_eq!(census.extra.len(), strict.device_census().unwrap().extra.len());
+}
+
+#[test]
+fn should_report_corrupt_census_dates() {
+    let census_hive = || {
+        let mut hive = TestHive::new();
+        hive.add_value(r"Root\DeviceCensus\OS", "OSOOBEDateTime", RegValue::SZ("2019-13-04T20:17:12.459".into()));
+        hive.add_value(r"Root\DeviceCensus\Firmware", "FirmwareReleaseDate", RegValue::SZ("#".into()));
+        hive
+    };
+    let census = census_hive().am_cache().device_census().unwrap();
+    assert_eq!("2019-13-04T20:17:12.459", census.os_oobe_date_time);
+    assert!(census.os_oobe_filetime.is_none());
+    assert!(census.anomalies.contains(&FieldAnomaly { value : r"OS\OSOOBEDateTime".into(), kind : AnomalyKind::Corrupt }));
+    // Unknown dates are stored as "#"
+    assert_eq!("", census.firmware_release_date);
+    assert!(!census.anomalies.iter().any(|v| v.value == r"Firmware\FirmwareReleaseDate" && v.kind == AnomalyKind::Corrupt));
+    let strict = AmCache::builder(census_hive()).root_key(RegHiveKey::HkeyLocalMachine).root_path("Amcache").parse_mode(ParseMode::Strict).build();
+    assert!(strict.device_census().is_err());
+}
+
+#[test]
+fn should_merge_driver_packages_extended() {
+    let am_cache = obtain_am_cache();
+    assert_eq!(0, am_cache.driver_packages_extended().unwrap().count());
+    let packages = am_cache.driver_packages_with_extended().unwrap();
+    assert_eq!(5, packages.len());
+    assert!(packages.iter().all(|v| v.extended.is_none()));
+    assert!(packages.iter().any(|v| v.key_name == "balloon.inf_amd64_b42039c1c5afb07d"));
+
+    let mut hive = TestHive::new();
+    hive.add_value(r"Root\InventoryDriverPackage\netkvm.inf_amd64_bfc7fe0145860ade", "Inf", RegValue::SZ("oem3.inf".into()));
+    hive.add_value(r"Root\InventoryDriverPackage\balloon.inf_amd64_b42039c1c5afb07d", "Inf", RegValue::SZ("oem4.inf".into()));
+    hive.add_value(r"Root\DriverPackageExtended\netkvm.inf_amd64_bfc7fe0145860ade", "DriverTargetRing", RegValue::DWord(2));
+    hive.add_value(r"Root\DriverPackageExtended\netkvm.inf_amd64_bfc7fe0145860ade", "Signer", RegValue::SZ("Microsoft Windows Hardware Compatibility Publisher".into()));
+    let am_cache = hive.am_cache();
+    let packages = am_cache.driver_packages_with_extended().unwrap();
+    let netkvm = packages.iter().find(|v| v.inf == "oem3.inf").unwrap();
+    let extended = netkvm.extended.as_ref().unwrap();
+    assert_eq!("2", extended.values["DriverTargetRing"]);
+    assert_eq!("Microsoft Windows Hardware Compatibility Publisher", extended.values["Signer"]);
+    assert!(packages.iter().find(|v| v.inf == "oem4.inf").unwrap().extended.is_none());
+}
+
+#[test]
+fn should_read_device_classes() {
+    let am_cache = obtain_am_cache();
+    let interfaces : Vec<_> = am_cache.device_interfaces().unwrap().collect();
+    assert_eq!(1, interfaces.len());
+    assert_eq!(0, interfaces[0].accelerometer_3d);
+    assert!(interfaces[0].anomalies.is_empty());
+    let hubs : Vec<_> = am_cache.device_usb_hubs().unwrap().collect();
+    assert_eq!(1, hubs.len());
+    assert_eq!(2, hubs[0].total_user_connectable_ports);
+    assert_eq!(0, hubs[0].total_user_connectable_type_c_ports);
+    assert_eq!(0, am_cache.device_media_classes().unwrap().count());
+
+    let mut hive = TestHive::new();
+    let media = r"Root\InventoryDeviceMediaClass\swd#mmdevapi#{0.0.0.00000000}.{4a4b4c4d-0000-4e4f-8a8b-1c2d3e4f5a6b}";
+    hive.add_value(media, "Audio_RenderDriver", RegValue::SZ("Speakers (High Definition Audio Device)".into()));
+    let am_cache = hive.am_cache();
+    let media = am_cache.device_media_classes().unwrap().next().unwrap();
+    assert_eq!("Speakers (High Definition Audio Device)", media.audio_render_driver);
+    assert_eq!("", media.audio_capture_driver);
+}
+
+#[test]
+fn should_read_office() {
+    let am_cache = obtain_am_cache();
+    let office = am_cache.office().unwrap();
+    assert_eq!(1, office.identifiers.len());
+    assert_eq!(1, office.products.len());
+    assert_eq!(9, office.products[0].state);
+    assert_eq!(1, office.settings.len());
+    assert_eq!(",,,", office.settings[0].exchange_provider_flags);
+    assert_eq!(1, office.ie_settings.len());
+    assert_eq!(0, office.ie_settings[0].o_ie_no_ax_install);
+    assert!(office.ie_settings[0].anomalies.is_empty());
+    assert!(office.add_ins.is_empty());
+    assert!(office.vba_rule_violations.is_empty());
+
+    let mut hive = TestHive::new();
+    let add_in = r"Root\InventoryMiscellaneousOfficeAddIn\{3ef8b5d2-6bd0-4fa5-9d5e-7b0c4bd8f1a1}";
+    hive.add_value(add_in, "FullPath", RegValue::SZ(r"C:\Users\Public\addin.dll".into()));
+    hive.add_value(add_in, "OfficeApplication", RegValue::SZ("Excel".into()));
+    hive.add_value(add_in, "LoadBehavior", RegValue::DWord(3));
+    hive.add_value(r"Root\InventoryMiscellaneousOfficeVBARuleViolations\Rule1", "Count", RegValue::DWord(4));
+    let office = hive.am_cache().office().unwrap();
+    assert_eq!(1, office.add_ins.len());
+    assert_eq!(r"C:\Users\Public\addin.dll", office.add_ins[0].full_path);
+    assert_eq!("Excel", office.add_ins[0].office_application);
+    assert_eq!(3, office.add_ins[0].load_behavior);
+    assert_eq!("Rule1", office.vba_rule_violations[0].rule_id);
+    assert_eq!(4, office.vba_rule_violations[0].count);
+    assert!(office.products.is_empty());
+}
+
+#[test]
+fn should_read_memory_slots_and_uup_info() {
+    let am_cache = obtain_am_cache();
+    assert!(matches!(am_cache.memory_slots(), Err(ForensicError::Missing(_))));
+    assert_eq!(0, am_cache.uup_info().unwrap().count());
+
+    let mut hive = TestHive::new();
+    let slot = r"Root\InventoryMiscellaneousMemorySlotArrayInfo\ChannelA-DIMM0";
+    hive.add_value(slot, "Capacity", RegValue::QWord(8589934592));
+    hive.add_value(slot, "Manufacturer", RegValue::SZ("Samsung".into()));
+    hive.add_value(slot, "Slot", RegValue::SZ("ChannelA-DIMM0".into()));
+    hive.add_value(slot, "Speed", RegValue::DWord(2667));
+    hive.add_value(slot, "Type", RegValue::DWord(26));
+    let uup = r"Root\InventoryMiscellaneousUUPInfo\Microsoft.Windows.ShellExperienceHost";
+    hive.add_value(uup, "Identifier", RegValue::SZ("Microsoft.Windows.ShellExperienceHost".into()));
+    hive.add_value(uup, "Version", RegValue::SZ("10.0.22621.1".into()));
+    hive.add_value(uup, "PreviousVersion", RegValue::SZ("10.0.19041.1".into()));
+    let am_cache = hive.am_cache();
+    let slots : Vec<_> = am_cache.memory_slots().unwrap().collect();
+    assert_eq!(1, slots.len());
+    assert_eq!(8589934592, slots[0].capacity);
+    assert_eq!("Samsung", slots[0].manufacturer);
+    assert_eq!(2667, slots[0].speed);
+    assert_eq!(26, slots[0].memory_type);
+    let uup : Vec<_> = am_cache.uup_info().unwrap().collect();
+    assert_eq!(1, uup.len());
+    assert_eq!("10.0.22621.1", uup[0].version);
+    assert_eq!("10.0.19041.1", uup[0].previous_version);
+    assert_eq!("", uup[0].source);
+}
+
+#[test]
+fn should_read_legacy_layout() {
+    let am_cache = obtain_am_cache();
+    assert!(matches!(am_cache.legacy_files(), Err(ForensicError::Missing(_))));
+
+    let mut hive = TestHive::new();
+    let volume = r"Root\File\{6d4ffa2c-5c33-11e3-824b-806e6f6e6963}";
+    let file = format!(r"{}\5000000011cd2", volume);
+    hive.add_value(&file, "0", RegValue::SZ("Microsoft® Windows® Operating System".into()));
+    hive.add_value(&file, "15", RegValue::SZ(r"C:\Windows\System32\calc.exe".into()));
+    hive.add_value(&file, "17", RegValue::QWord(130_225_116_740_000_000));
+    hive.add_value(&file, "101", RegValue::SZ("00009bd0da8db4d8b3a8ad0cdb3a2d2f6b15e8ba4a1b".into()));
+    hive.add_value(&file, "100", RegValue::SZ("0000f519feec486de87ed73cb92d3cac802400000000".into()));
+    hive.add_value(&format!(r"{}\2000000000a1e", volume), "15", RegValue::SZ(r"C:\Windows\notepad.exe".into()));
+    hive.add_value(r"Root\File\{9b4a5c21-0000-0000-0000-100000000000}\1000000000055", "15", RegValue::SZ(r"D:\setup.exe".into()));
+    let program = r"Root\Programs\0000f519feec486de87ed73cb92d3cac802400000000";
+    hive.add_value(program, "0", RegValue::SZ("Windows".into()));
+    hive.add_value(program, "6", RegValue::SZ("AddRemoveProgram".into()));
+    hive.add_value(program, "a", RegValue::DWord(1247534074));
+    hive.add_value(program, "Files", RegValue::MultiSZ(vec!["{6d4ffa2c-5c33-11e3-824b-806e6f6e6963}@5000000011cd2".into()]));
+    let am_cache = hive.am_cache();
+
+    let files : Vec<_> = am_cache.legacy_files().unwrap().collect();
+    assert_eq!(3, files.len());
+    let calc = files.iter().find(|v| v.file_reference == 0x5000000011cd2).unwrap();
+    assert_eq!("{6d4ffa2c-5c33-11e3-824b-806e6f6e6963}", calc.volume_guid);
+    assert_eq!(0x11cd2, calc.mft_entry_number);
+    assert_eq!(5, calc.mft_sequence_number);
+    assert_eq!(r"C:\Windows\System32\calc.exe", calc.full_path);
+    assert_eq!("Microsoft® Windows® Operating System", calc.product_name);
+    assert_eq!("9bd0da8db4d8b3a8ad0cdb3a2d2f6b15e8ba4a1b", calc.sha1().unwrap().to_string());
+    assert_eq!(130_225_116_740_000_000, calc.last_modified.unwrap().filetime());
+    assert!(calc.created.is_none());
+    assert_eq!("{9b4a5c21-0000-0000-0000-100000000000}", files.iter().find(|v| v.full_path == r"D:\setup.exe").unwrap().volume_guid);
+
+    let programs : Vec<_> = am_cache.legacy_programs().unwrap().collect();
+    assert_eq!(1, programs.len());
+    assert_eq!(calc.program_id, programs[0].program_id);
+    assert_eq!("Windows", programs[0].name);
+    assert_eq!("AddRemoveProgram", programs[0].entry_type);
+    assert_eq!((1247534074 + 11_644_473_600) * 10_000_000, programs[0].install_date.unwrap().filetime());
+    assert_eq!(vec![format!("{}@{:x}", calc.volume_guid, calc.file_reference)], programs[0].files);
+}
+
+#[test]
+fn should_keep_key_names() {
+    let am_cache = obtain_am_cache();
+    let ohci = am_cache.driver_binaries().unwrap().find(|v| v.driver_name == "1394ohci.sys").unwrap();
+    assert_eq!("c:/windows/system32/drivers/1394ohci.sys", ohci.key_name);
+    assert_eq!(r"Root\InventoryDriverBinary\c:/windows/system32/drivers/1394ohci.sys", ohci.key_path);
+    for app in am_cache.applications().unwrap() {
+        assert_eq!(app.program_id, app.key_name);
+        assert_eq!(format!(r"Root\InventoryApplication\{}", app.program_id), app.key_path);
+    }
+    for file in am_cache.application_files().unwrap() {
+        assert_eq!(file.long_path_hash, file.key_name);
+    }
+    assert!(am_cache.device_pnps().unwrap().all(|v| v.key_path.starts_with(r"Root\InventoryDevicePnp\") && !v.key_name.is_empty()));
+    assert_eq!(r"Root\DeviceCensus", am_cache.device_census().unwrap().key_path);
+    assert_eq!(r"Root\InventoryMiscellaneousOfficeProducts\OfficeProducts", am_cache.office().unwrap().products[0].key_path);
+
+    let mut hive = TestHive::new();
+    hive.add_value(r"Root\File\{6d4ffa2c-5c33-11e3-824b-806e6f6e6963}\5000000011cd2", "15", RegValue::SZ(r"C:\Windows\System32\calc.exe".into()));
+    let file = hive.am_cache().legacy_files().unwrap().next().unwrap();
+    assert_eq!("5000000011cd2", file.key_name);
+    assert_eq!(r"Root\File\{6d4ffa2c-5c33-11e3-824b-806e6f6e6963}\5000000011cd2", file.key_path);
+}
+
+#[test]
+fn should_keep_unmapped_values() {
+    let am_cache = obtain_am_cache();
+    // Icon is mapped and DriverTimeStamp is a stale entry of the value list
+    assert!(am_cache.device_containers().unwrap().all(|v| v.extra.is_empty()));
+    assert!(am_cache.application_files().unwrap().all(|v| v.extra.is_empty()));
+    // The value list of this key has a stale entry after the last value
+    let acpiex = am_cache.driver_binaries().unwrap().find(|v| v.driver_name == "acpiex.sys").unwrap();
+    assert!(acpiex.extra.is_empty());
+    assert!(am_cache.device_census().unwrap().extra.contains_key(r"App\CensusVersion"));
+
+    let mut hive = TestHive::new();
+    let hub = r"Root\InventoryDeviceUsbHubClass\DeviceUsbHubClass";
+    hive.add_value(hub, "TotalUserConnectablePorts", RegValue::DWord(4));
+    hive.add_value(hub, "TotalUserConnectableUsb4Ports", RegValue::DWord(2));
+    hive.add_value(hub, "HubFlags", RegValue::Binary(vec![0xca, 0xfe]));
+    let hub = hive.am_cache().device_usb_hubs().unwrap().next().unwrap();
+    assert_eq!(4, hub.total_user_connectable_ports);
+    assert_eq!(2, hub.extra.len());
+    assert_eq!(Some(&RegValue::DWord(2)), hub.extra.get("TotalUserConnectableUsb4Ports"));
+    assert_eq!(Some(&RegValue::Binary(vec![0xca, 0xfe])), hub.extra.get("HubFlags"));
+
+    #[cfg(feature = "serde")]
+    {
+        use crate::common::dev_usbhub::InventoryDeviceUsbHubClass;
+        let json = serde_json::to_value(&hub).unwrap();
+        assert_eq!("REG_DWORD", json["extra"]["TotalUserConnectableUsb4Ports"]["type"]);
+        assert_eq!(2, json["extra"]["TotalUserConnectableUsb4Ports"]["data"]);
+        assert_eq!("cafe", json["extra"]["HubFlags"]["data"]);
+        let parsed : InventoryDeviceUsbHubClass = serde_json::from_value(json).unwrap();
+        assert_eq!(hub.extra, parsed.extra);
+    }
+}
+
 #[test]
 fn should_iterate_over_shortcuts() {
     let am_cache = obtain_am_cache();
@@ -46,4 +708,183 @@ fn should_iterate_over_shortcuts() {
     for driver_pkg in am_cache.driver_package().unwrap() {
         println!("{:?}", driver_pkg);
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn should_read_with_schema() {
+    let am_cache = obtain_am_cache();
+    let schema = Schema::builtin("InventoryApplicationFile").unwrap();
+    let records : Vec<_> = am_cache.schema_records(&schema).unwrap().collect();
+    let files : Vec<_> = am_cache.application_files().unwrap().collect();
+    assert_eq!(files.len(), records.len());
+    for (file, record) in files.iter().zip(&records) {
+        assert_eq!(file.key_path, record.key_path);
+        assert_eq!(Some(file.path.as_str()), record.get("path").and_then(|v| v.as_str()));
+        assert_eq!(Some(file.size), record.get("size").and_then(|v| v.as_u64()));
+        assert_eq!(file.link_date_filetime.map(|v| v.filetime()), record.get("link_date").and_then(|v| v.as_filetime()).map(|v| v.filetime()));
+        assert_eq!(file.extra.keys().collect::<Vec<_>>(), record.extra.keys().collect::<Vec<_>>());
+    }
+
+    let mut hive = TestHive::new();
+    let entry = r"Root\InventoryFutureKey\0000abcd";
+    hive.add_value(entry, "BinProductVersion", RegValue::SZ("1.2.3".into()));
+    hive.add_value(entry, "FirstSeen", RegValue::DWord(1247534074));
+    hive.add_value(entry, "Flags", RegValue::DWord(7));
+    let am_cache = hive.am_cache();
+    let schema = Schema::new("InventoryFutureKey")
+        .field(FieldDescriptor::new("product_version", "ProductVersion", FieldType::String).alias("BinProductVersion"))
+        .field(FieldDescriptor::new("first_seen", "FirstSeen", FieldType::UnixTime));
+    let records : Vec<_> = am_cache.schema_records(&schema).unwrap().collect();
+    assert_eq!(1, records.len());
+    assert_eq!(r"Root\InventoryFutureKey\0000abcd", records[0].key_path);
+    assert_eq!(Some("1.2.3"), records[0].get("product_version").and_then(|v| v.as_str()));
+    assert_eq!(Some(128_920_076_740_000_000), records[0].get("first_seen").and_then(|v| v.as_filetime()).map(|v| v.filetime()));
+    assert_eq!(vec!["Flags"], records[0].extra.keys().collect::<Vec<_>>());
+    assert!(records[0].anomalies.is_empty());
+    assert!(matches!(am_cache.schema_records(&Schema::new("InventoryMissingKey")), Err(ForensicError::Missing(_))));
+
+    let mut schema = Schema::new("InventoryFutureKey").field(FieldDescriptor::new("flags", "Flags", FieldType::U32));
+    schema.other_values = Some(FieldType::Text);
+    let record = am_cache.schema_records(&schema).unwrap().next().unwrap();
+    assert_eq!(Some(7), record.get("flags").and_then(|v| v.as_u64()));
+    assert_eq!(Some("1247534074"), record.other.get("FirstSeen").and_then(|v| v.as_str()));
+    assert_eq!(Some("1.2.3"), record.other.get("BinProductVersion").and_then(|v| v.as_str()));
+    assert!(record.extra.is_empty());
+
+    for key in Schema::builtin_keys() {
+        assert_eq!(key, Schema::builtin(key).unwrap().key);
+    }
+    assert!(Schema::builtin("File").is_none());
+    let schema = Schema::builtin("InventoryApplication").unwrap();
+    assert!(schema.fields.iter().any(|v| v.name == "type" && v.value == "Type"));
+}
+
+#[cfg(all(feature = "json", feature = "toml"))]
+#[test]
+fn should_load_schema_files() {
+    let toml = r#"
+key = "InventoryApplicationFile"
+
+[[fields]]
+name = "product_version"
+value = "ProductVersion"
+aliases = ["BinProductVersion"]
+type = "string"
+
+[[fields]]
+name = "link_date"
+value = "LinkDate"
+type = "date"
+"#;
+    let json = r#"{"key":"InventoryApplicationFile","fields":[
+        {"name":"product_version","value":"ProductVersion","aliases":["BinProductVersion"],"type":"string"},
+        {"name":"link_date","value":"LinkDate","type":"date"}
+    ]}"#;
+    let schema = Schema::from_toml(toml).unwrap();
+    assert_eq!(schema, Schema::from_json(json).unwrap());
+    assert_eq!(vec!["BinProductVersion".to_string()], schema.fields[0].aliases);
+    assert_eq!(FieldType::Date, schema.fields[1].field_type);
+    assert!(Schema::from_json(r#"{"key":"InventoryApplicationFile","fields":[{"name":"a","value":"A","type":"float"}]}"#).is_err());
+
+    let am_cache = obtain_am_cache();
+    let record = am_cache.schema_records(&schema).unwrap().next().unwrap();
+    assert_eq!(2, record.fields.len());
+    let json = serde_json::to_value(&record).unwrap();
+    assert_eq!("date", json["fields"]["link_date"]["type"]);
+    assert_eq!("string", json["fields"]["product_version"]["type"]);
+}
+
+#[test]
+fn should_detect_format_generation() {
+    let info = obtain_am_cache().format_info().unwrap();
+    assert_eq!(AmCacheGeneration::Inventory, info.generation);
+    assert_eq!(24, info.subkeys.len());
+    assert!(info.has_subkey("InventoryApplicationFile"));
+    assert!(info.unsupported_subkeys().is_empty());
+    assert_eq!(Schema::builtin_keys().count() + 2, supported_subkeys().count());
+    assert!(supported_subkeys().any(|v| v == "DeviceCensus") && supported_subkeys().any(|v| v == "File"));
+    let os_version = info.os_version.unwrap();
+    assert_eq!(14393, os_version.build);
+    assert_eq!("10.0.14393", os_version.version);
+    assert_eq!("10.0.14393.3179", os_version.raw);
+    assert_eq!(OsVersionSource::InBoxDriver, os_version.source);
+
+    let mut hive = TestHive::new();
+    hive.add_value(r"Root\File\{6d4ffa2c-5c33-11e3-824b-806e6f6e6963}\5000000011cd2", "15", RegValue::SZ(r"C:\Windows\System32\calc.exe".into()));
+    hive.add_value(r"Root\Programs\0000f519feec486de87ed73cb92d3cac802400000000", "0", RegValue::SZ("Windows".into()));
+    let info = hive.am_cache().format_info().unwrap();
+    assert_eq!(AmCacheGeneration::Legacy, info.generation);
+    assert_eq!(None, info.os_version);
+
+    let mut hive = TestHive::new();
+    hive.add_value(r"Root\File\{6d4ffa2c-5c33-11e3-824b-806e6f6e6963}\5000000011cd2", "15", RegValue::SZ(r"C:\Windows\System32\calc.exe".into()));
+    hive.add_value(r"Root\InventoryApplication\0000f519feec486de87ed73cb92d3cac802400000000", "OSVersionAtInstallTime", RegValue::SZ("10.0.0.14393".into()));
+    let info = hive.am_cache().format_info().unwrap();
+    assert_eq!(AmCacheGeneration::Mixed, info.generation);
+    assert_eq!(Some(14393), info.os_version.map(|v| v.build));
+
+    let mut hive = TestHive::new();
+    hive.add_value(r"Root\InventoryApplication\0000a1", "OSVersionAtInstallTime", RegValue::SZ("10.0.0.19045".into()));
+    hive.add_value(r"Root\InventoryApplication\0000a2", "OSVersionAtInstallTime", RegValue::SZ("10.0.22631.0".into()));
+    hive.add_value(r"Root\InventoryApplication\0000a3", "OSVersionAtInstallTime", RegValue::SZ("".into()));
+    hive.add_value(r"Root\InventoryFutureKey\0000abcd", "Flags", RegValue::DWord(7));
+    let info = hive.am_cache().format_info().unwrap();
+    assert_eq!(AmCacheGeneration::Windows11, info.generation);
+    assert_eq!(vec!["InventoryFutureKey"], info.unsupported_subkeys());
+    let os_version = info.os_version.unwrap();
+    assert_eq!(22631, os_version.build);
+    assert_eq!("10.0.22631", os_version.version);
+    assert_eq!("10.0.22631.0", os_version.raw);
+    assert_eq!(OsVersionSource::OsVersionAtInstallTime, os_version.source);
+
+    let mut hive = TestHive::new();
+    hive.add_value(r"Root\InventoryApplication\0000a1", "OSVersionAtInstallTime", RegValue::SZ("10.0.0.19045".into()));
+    hive.add_value(r"Root\InventoryDriverBinary\acpi.sys", "DriverVersion", RegValue::SZ("10.0.22621.2506".into()));
+    hive.add_value(r"Root\InventoryDriverBinary\acpi.sys", "DriverInBox", RegValue::DWord(1));
+    hive.add_value(r"Root\InventoryDriverBinary\vendor.sys", "DriverVersion", RegValue::SZ("10.0.26100.1".into()));
+    hive.add_value(r"Root\InventoryDriverBinary\vendor.sys", "DriverInBox", RegValue::DWord(0));
+    let info = hive.am_cache().format_info().unwrap();
+    assert_eq!(AmCacheGeneration::Windows11, info.generation);
+    let os_version = info.os_version.unwrap();
+    assert_eq!("10.0.22621", os_version.version);
+    assert_eq!(OsVersionSource::InBoxDriver, os_version.source);
+
+    // Windows 8.1 updated with a recent compatibility appraiser
+    let mut hive = TestHive::new();
+    hive.add_value(r"Root\InventoryApplication\0000a1", "OSVersionAtInstallTime", RegValue::SZ("6.3.9600.0".into()));
+    hive.add_value(r"Root\InventoryApplication\0000a2", "OSVersionAtInstallTime", RegValue::SZ("11.0.9600.19400".into()));
+    let info = hive.am_cache().format_info().unwrap();
+    assert_eq!(AmCacheGeneration::Downlevel, info.generation);
+    let os_version = info.os_version.unwrap();
+    assert_eq!(9600, os_version.build);
+    assert_eq!("6.3.9600", os_version.version);
+    assert_eq!("6.3.9600.0", os_version.raw);
+    assert_eq!(OsVersionSource::OsVersionAtInstallTime, os_version.source);
+
+    let mut hive = TestHive::new();
+    hive.add_value(r"Root\File\{6d4ffa2c-5c33-11e3-824b-806e6f6e6963}\5000000011cd2", "15", RegValue::SZ(r"C:\Windows\System32\calc.exe".into()));
+    hive.add_value(r"Root\InventoryDriverBinary\acpi.sys", "DriverVersion", RegValue::SZ("6.1.7601.17514".into()));
+    hive.add_value(r"Root\InventoryDriverBinary\acpi.sys", "DriverInBox", RegValue::DWord(1));
+    let info = hive.am_cache().format_info().unwrap();
+    assert_eq!(AmCacheGeneration::Downlevel, info.generation);
+    assert_eq!("6.1.7601", info.os_version.unwrap().version);
+
+    let mut hive = TestHive::new();
+    hive.add_value(r"Root\InventoryApplication\0000a1", "Name", RegValue::SZ("Notepad++".into()));
+    hive.add_value(r"Root\DeviceCensus\App", "IEVersion", RegValue::SZ("11.2273.14393.0".into()));
+    let os_version = hive.am_cache().format_info().unwrap().os_version.unwrap();
+    assert_eq!("10.0.14393", os_version.version);
+    assert_eq!("11.2273.14393.0", os_version.raw);
+    assert_eq!(OsVersionSource::IeVersion, os_version.source);
+
+    let mut hive = TestHive::new();
+    hive.add_value(r"Root\InventoryApplication\0000a1", "Name", RegValue::SZ("Notepad++".into()));
+    hive.add_value(r"Root\DeviceCensus\App", "IEVersion", RegValue::SZ("11.0.9600.19400".into()));
+    assert_eq!(None, hive.am_cache().format_info().unwrap().os_version);
+
+    let mut hive = TestHive::new();
+    hive.add_value(r"Root\Unknown\0000abcd", "Flags", RegValue::DWord(7));
+    let info = hive.am_cache().format_info().unwrap();
+    assert_eq!(AmCacheGeneration::Unknown, info.generation);
+    assert_eq!(vec!["Unknown"], info.unsupported_subkeys());
+}